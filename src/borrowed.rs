@@ -0,0 +1,412 @@
+/*!
+Support for validating an address without taking ownership of, or copying, the string.
+
+An [EmailAddressRef] is parsed with the same [Options](crate::Options) as an
+[EmailAddress](crate::EmailAddress), and records the same component boundaries, but borrows the
+address from the caller. This is useful where many addresses are validated and inspected, for
+example when scanning logs, and only a few, if any, need to be kept. An `EmailAddressRef` can be
+converted into an owned `EmailAddress` with `to_owned`, and any `EmailAddress` can be borrowed
+as an `EmailAddressRef` with `as_email_ref`.
+
+Parsing, comparing, and hashing an `EmailAddressRef` does not allocate, with two exceptions where
+a domain is converted between IDNA forms: validating an A-label, one beginning `xn--`, and
+checking that a Unicode domain can be written as A-labels when SMTPUTF8 is not allowed.
+*/
+
+use crate::{
+    cmp_domain_values, cmp_local_part_values, decode_display_name, fold_domain,
+    folded_domain_chars, hash_chars, local_part_value_chars, parse_address_ref,
+    parse_address_ref_detailed, parse_host, strip_cfws, unquote_local_part, EmailAddress, Error,
+    Host, Options, ParseError, Parts,
+};
+#[cfg(feature = "serde_support")]
+use serde::{Deserialize, Serialize, Serializer};
+use std::borrow::Cow;
+use std::cmp::Ordering;
+use std::convert::TryFrom;
+use std::fmt::{Display, Formatter};
+use std::hash::{Hash, Hasher};
+
+// ------------------------------------------------------------------------------------------------
+// Public Types
+// ------------------------------------------------------------------------------------------------
+
+///
+/// Type representing a single email address borrowed from a string, with the same accessors as
+/// [EmailAddress](crate::EmailAddress) but without any allocation to store the address.
+///
+/// ```rust
+/// use email_address::*;
+///
+/// let line = "delivered to Simon <simon@example.com>";
+/// let email = EmailAddressRef::parse_with_options(
+///     &line[13..],
+///     Options::default().with_display_text(),
+/// ).unwrap();
+///
+/// assert_eq!(email.display_part(), "Simon");
+/// assert_eq!(email.local_part(), "simon");
+/// assert_eq!(email.domain(), "example.com");
+///
+/// let owned: EmailAddress = email.to_owned();
+/// assert_eq!(owned.as_email_ref(), email);
+/// ```
+///
+#[derive(Debug, Clone, Copy)]
+pub struct EmailAddressRef<'a> {
+    address: &'a str,
+    parts: Parts,
+}
+
+// ------------------------------------------------------------------------------------------------
+// Implementations
+// ------------------------------------------------------------------------------------------------
+
+impl Display for EmailAddressRef<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.address)
+    }
+}
+
+// Equality, hashing, and ordering are shared with `EmailAddress`, so that an
+// address and its borrowed form behave identically in collections. Each uses
+// the unquoted `local-part`, which is case-sensitive, and the case-folded
+// `domain`, compared without copying either.
+
+impl PartialEq for EmailAddressRef<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for EmailAddressRef<'_> {}
+
+impl Hash for EmailAddressRef<'_> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        hash_chars(local_part_value_chars(self.local_part()), state);
+        hash_chars(folded_domain_chars(self.domain()), state);
+    }
+}
+
+impl PartialOrd for EmailAddressRef<'_> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for EmailAddressRef<'_> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        cmp_local_part_values(self.local_part(), other.local_part())
+            .then_with(|| cmp_domain_values(self.domain(), other.domain()))
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl<'a> TryFrom<&'a str> for EmailAddressRef<'a> {
+    type Error = Error;
+
+    fn try_from(s: &'a str) -> Result<Self, Self::Error> {
+        Self::parse_with_options(s, Default::default())
+    }
+}
+
+impl<'a> From<&'a EmailAddress> for EmailAddressRef<'a> {
+    fn from(email: &'a EmailAddress) -> Self {
+        email.as_email_ref()
+    }
+}
+
+impl From<EmailAddressRef<'_>> for EmailAddress {
+    fn from(email: EmailAddressRef<'_>) -> Self {
+        email.to_owned()
+    }
+}
+
+impl AsRef<str> for EmailAddressRef<'_> {
+    fn as_ref(&self) -> &str {
+        self.address
+    }
+}
+
+#[cfg(feature = "serde_support")]
+impl Serialize for EmailAddressRef<'_> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(self.address)
+    }
+}
+
+#[cfg(feature = "serde_support")]
+impl<'de> Deserialize<'de> for EmailAddressRef<'de> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        use serde::de::{Error, Unexpected, Visitor};
+
+        struct EmailAddressRefVisitor;
+
+        impl<'de> Visitor<'de> for EmailAddressRefVisitor {
+            type Value = EmailAddressRef<'de>;
+
+            fn expecting(&self, fmt: &mut Formatter<'_>) -> std::fmt::Result {
+                fmt.write_str("borrowed string containing a valid email address")
+            }
+
+            fn visit_borrowed_str<E>(self, s: &'de str) -> Result<Self::Value, E>
+            where
+                E: Error,
+            {
+                EmailAddressRef::try_from(s).map_err(|err| {
+                    let exp = format!("{}", err);
+                    Error::invalid_value(Unexpected::Str(s), &exp.as_ref())
+                })
+            }
+        }
+
+        deserializer.deserialize_str(EmailAddressRefVisitor)
+    }
+}
+
+impl<'a> EmailAddressRef<'a> {
+    ///
+    /// Creates an `EmailAddressRef` without checking if the email is valid. Only
+    /// call this method if the address is known to be valid.
+    ///
+    pub fn new_unchecked(address: &'a str) -> Self {
+        Self {
+            address,
+            parts: Parts::new(address),
+        }
+    }
+
+    ///
+    /// Creates an `EmailAddressRef` from the already recorded boundaries of its components.
+    ///
+    pub(crate) const fn from_parts(address: &'a str, parts: Parts) -> Self {
+        Self { address, parts }
+    }
+
+    ///
+    /// Parses an [EmailAddressRef] with custom [Options], as
+    /// [EmailAddress::parse_with_options](crate::EmailAddress::parse_with_options).
+    ///
+    /// ```rust
+    /// use email_address::*;
+    ///
+    /// assert!(EmailAddressRef::parse_with_options("simon@localhost", Options::default()).is_ok());
+    /// assert_eq!(
+    ///     EmailAddressRef::parse_with_options("simon@", Options::default()),
+    ///     Err(Error::DomainEmpty)
+    /// );
+    /// ```
+    ///
+    pub fn parse_with_options(address: &'a str, options: Options) -> Result<Self, Error> {
+        parse_address_ref(address, options)
+    }
+
+    ///
+    /// Parses an [EmailAddressRef] with custom [Options], returning any error as a
+    /// [ParseError], as [EmailAddress::parse_detailed](crate::EmailAddress::parse_detailed).
+    ///
+    pub fn parse_detailed(address: &'a str, options: Options) -> Result<Self, ParseError> {
+        parse_address_ref_detailed(address, options)
+    }
+
+    ///
+    /// Returns an owned [EmailAddress](crate::EmailAddress), copying the address string but
+    /// without parsing it again.
+    ///
+    pub fn to_owned(&self) -> EmailAddress {
+        EmailAddress {
+            address: self.address.to_string(),
+            parts: self.parts,
+        }
+    }
+
+    ///
+    /// Returns the `local-part` of the email address.
+    ///
+    pub fn local_part(&self) -> &'a str {
+        &self.address[self.parts.local_start..self.parts.separator]
+    }
+
+    ///
+    /// Returns the value of the `local-part`, as
+    /// [EmailAddress::local_part_unquoted](crate::EmailAddress::local_part_unquoted).
+    ///
+    pub fn local_part_unquoted(&self) -> Cow<'a, str> {
+        match strip_cfws(self.local_part()) {
+            Ok(Cow::Owned(local_part)) => Cow::Owned(unquote_local_part(&local_part).into_owned()),
+            _ => unquote_local_part(self.local_part()),
+        }
+    }
+
+    ///
+    /// Returns the display part of the email address.
+    ///
+    pub fn display_part(&self) -> &'a str {
+        &self.address[self.parts.display_start..self.parts.display_end]
+    }
+
+    ///
+    /// Returns the display name of the email address, decoded from the raw `display_part`, as
+    /// [EmailAddress::display_name](crate::EmailAddress::display_name).
+    ///
+    pub fn display_name(&self) -> Cow<'a, str> {
+        decode_display_name(self.display_part())
+    }
+
+    ///
+    /// Returns the email part of the email address, without any display part.
+    ///
+    pub fn email(&self) -> &'a str {
+        &self.address[self.parts.local_start..self.parts.domain_end]
+    }
+
+    ///
+    /// Returns the domain of the email address.
+    ///
+    pub fn domain(&self) -> &'a str {
+        &self.address[self.parts.domain_start..self.parts.domain_end]
+    }
+
+    ///
+    /// Returns the domain of the email address parsed into a [Host], as
+    /// [EmailAddress::host](crate::EmailAddress::host).
+    ///
+    pub fn host(&self) -> Host<'a> {
+        parse_host(self.domain())
+    }
+
+    ///
+    /// Returns the entire email address as a string reference.
+    ///
+    pub fn as_str(&self) -> &'a str {
+        self.address
+    }
+
+    ///
+    /// Split the `local-part` into the base and detail of an RFC 5233 subaddress, as
+    /// [EmailAddress::split_subaddress](crate::EmailAddress::split_subaddress).
+    ///
+    pub fn split_subaddress(&self, separator: char) -> (Cow<'a, str>, Option<Cow<'a, str>>) {
+        match self.local_part_unquoted() {
+            Cow::Borrowed(value) => match value.split_once(separator) {
+                Some((base, detail)) => (Cow::Borrowed(base), Some(Cow::Borrowed(detail))),
+                None => (Cow::Borrowed(value), None),
+            },
+            Cow::Owned(value) => match value.split_once(separator) {
+                Some((base, detail)) => (
+                    Cow::Owned(base.to_string()),
+                    Some(Cow::Owned(detail.to_string())),
+                ),
+                None => (Cow::Owned(value), None),
+            },
+        }
+    }
+
+    ///
+    /// Returns the detail of an RFC 5233 subaddress, as `split_subaddress`, if present.
+    ///
+    pub fn subaddress(&self, separator: char) -> Option<Cow<'a, str>> {
+        self.split_subaddress(separator).1
+    }
+
+    ///
+    /// Returns the `domain` without any CFWS and case-folded, as used for comparison.
+    ///
+    pub(crate) fn folded_domain(&self) -> Cow<'a, str> {
+        match strip_cfws(self.domain()) {
+            Ok(Cow::Owned(domain)) => Cow::Owned(fold_domain(&domain).into_owned()),
+            _ => fold_domain(self.domain()),
+        }
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Unit Tests
+// ------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    #[test]
+    fn test_accessors_match_owned() {
+        let options = Options::default().with_display_text().with_comments();
+        for address in &[
+            "simon@example.com",
+            "Simon <simon(work)@example.com>",
+            "\"simon johnston\"@[IPv6:2001:db8::1]",
+            "=?UTF-8?B?S8O2bmln?= <koenig+lists@example.org>",
+        ] {
+            let borrowed = EmailAddressRef::parse_with_options(address, options).unwrap();
+            let owned = EmailAddress::parse_with_options(address, options).unwrap();
+
+            assert_eq!(borrowed.as_str(), owned.as_str());
+            assert_eq!(borrowed.local_part(), owned.local_part());
+            assert_eq!(borrowed.display_part(), owned.display_part());
+            assert_eq!(borrowed.display_name(), owned.display_name());
+            assert_eq!(borrowed.email(), owned.email());
+            assert_eq!(borrowed.domain(), owned.domain());
+            assert_eq!(borrowed.host(), owned.host());
+            assert_eq!(borrowed.split_subaddress('+'), owned.split_subaddress('+'));
+            assert_eq!(borrowed, owned.as_email_ref());
+            assert_eq!(borrowed.to_owned(), owned);
+        }
+    }
+
+    #[test]
+    fn test_errors_match_owned() {
+        for address in &[
+            "",
+            "simon",
+            "simon@",
+            "sim,on@example.com",
+            "simon@example..com",
+        ] {
+            assert_eq!(
+                EmailAddressRef::parse_detailed(address, Options::default()).unwrap_err(),
+                EmailAddress::parse_detailed(address, Options::default()).unwrap_err()
+            );
+            assert_eq!(
+                EmailAddressRef::try_from(*address).unwrap_err(),
+                EmailAddress::from_str(address).unwrap_err()
+            );
+        }
+    }
+
+    #[test]
+    fn test_borrows_input() {
+        let address = String::from("simon@example.com");
+        let email = EmailAddressRef::try_from(address.as_str()).unwrap();
+
+        assert!(std::ptr::eq(email.as_str(), address.as_str()));
+        assert!(std::ptr::eq(email.domain(), &address[6..]));
+    }
+
+    #[cfg(feature = "serde_support")]
+    #[test]
+    fn test_serde_borrowed() {
+        use serde_assert::{Deserializer, Serializer, Token};
+
+        let email = EmailAddressRef::try_from("simon@example.com").unwrap();
+        let serializer = Serializer::builder().build();
+        assert_eq!(
+            email.serialize(&serializer).unwrap(),
+            [Token::Str("simon@example.com".to_string())]
+        );
+
+        let tokens = [Token::Str("simon@example.com".to_string())];
+        let mut deserializer = Deserializer::builder(tokens).zero_copy(true).build();
+        assert_eq!(
+            EmailAddressRef::deserialize(&mut deserializer).unwrap(),
+            email
+        );
+    }
+}
@@ -0,0 +1,187 @@
+/*!
+Support for assembling an address from its unescaped components.
+
+The [EmailAddressBuilder] takes the value of each component, rather than its text as written in
+an address, and adds any quoting or encoding required; the `local-part` is quoted if it is not
+a valid `dot-atom`, with `"` and `\` written as quoted-pairs, and the display name is written
+as an RFC 5322 `phrase`. The assembled address is then validated against [Options].
+*/
+
+use crate::{
+    encode_display_name, local_part_from_value, parse_address, EmailAddress, Error, Options, AT,
+    DISPLAY_END, DISPLAY_START,
+};
+
+// ------------------------------------------------------------------------------------------------
+// Public Types
+// ------------------------------------------------------------------------------------------------
+
+///
+/// A builder for an [EmailAddress], from an unescaped `local-part`, a `domain`, and an optional
+/// display name.
+///
+/// ```rust
+/// use email_address::*;
+///
+/// let email = EmailAddressBuilder::new()
+///     .local_part("john doe")
+///     .domain("example.com")
+///     .display_name("Doe, John")
+///     .build()
+///     .unwrap();
+///
+/// assert_eq!(email.as_str(), "\"Doe, John\" <\"john doe\"@example.com>");
+/// assert_eq!(email.display_name(), "Doe, John");
+///
+/// assert_eq!(
+///     EmailAddressBuilder::new()
+///         .local_part("john")
+///         .domain("[192.168.2.1]")
+///         .options(Options::default().without_domain_literal())
+///         .build(),
+///     Err(Error::UnsupportedDomainLiteral)
+/// );
+/// ```
+///
+#[derive(Debug, Clone, Default)]
+pub struct EmailAddressBuilder {
+    display_name: String,
+    local_part: String,
+    domain: String,
+    options: Options,
+}
+
+// ------------------------------------------------------------------------------------------------
+// Implementations
+// ------------------------------------------------------------------------------------------------
+
+impl EmailAddressBuilder {
+    ///
+    /// Construct a builder with empty components and the default [Options].
+    ///
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    ///
+    /// Set the value of the `local-part`, which is quoted and escaped when the address is built
+    /// if required.
+    ///
+    pub fn local_part(self, local_part: &str) -> Self {
+        Self {
+            local_part: local_part.to_string(),
+            ..self
+        }
+    }
+
+    ///
+    /// Set the `domain`, either a domain name or a domain literal such as `[192.168.2.1]`.
+    ///
+    pub fn domain(self, domain: &str) -> Self {
+        Self {
+            domain: domain.to_string(),
+            ..self
+        }
+    }
+
+    ///
+    /// Set the display name, which is written as a `phrase` when the address is built. An
+    /// empty display name, the default, is omitted.
+    ///
+    pub fn display_name(self, display_name: &str) -> Self {
+        Self {
+            display_name: display_name.to_string(),
+            ..self
+        }
+    }
+
+    ///
+    /// Set the [Options] used to validate the address when it is built.
+    ///
+    pub fn options(self, options: Options) -> Self {
+        Self { options, ..self }
+    }
+
+    ///
+    /// Assemble and validate the address, returning an error if it is not valid with the
+    /// builder's [Options].
+    ///
+    pub fn build(&self) -> Result<EmailAddress, Error> {
+        let local_part = local_part_from_value(&self.local_part);
+        let display_name = encode_display_name(&self.display_name, false);
+        let address = if display_name.is_empty() {
+            format!("{}{}{}", local_part, AT, self.domain)
+        } else {
+            format!(
+                "{} {}{}{}{}{}",
+                display_name, DISPLAY_START, local_part, AT, self.domain, DISPLAY_END
+            )
+        };
+        parse_address(&address, self.options)
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Unit Tests
+// ------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn built(local_part: &str, domain: &str, display_name: &str) -> Result<String, Error> {
+        EmailAddressBuilder::new()
+            .local_part(local_part)
+            .domain(domain)
+            .display_name(display_name)
+            .build()
+            .map(|email| email.to_string())
+    }
+
+    #[test]
+    fn test_quoting() {
+        assert_eq!(
+            built("john.doe", "example.com", ""),
+            Ok("john.doe@example.com".to_string())
+        );
+        assert_eq!(
+            built("john doe", "example.com", ""),
+            Ok("\"john doe\"@example.com".to_string())
+        );
+        assert_eq!(
+            built("john \"jack\" d\\oe", "example.com", ""),
+            Ok("\"john \\\"jack\\\" d\\\\oe\"@example.com".to_string())
+        );
+        assert_eq!(
+            built(".john", "example.com", ""),
+            Ok("\".john\"@example.com".to_string())
+        );
+        assert_eq!(
+            built("john@home", "example.com", "John \"Jack\" Doe"),
+            Ok("\"John \\\"Jack\\\" Doe\" <\"john@home\"@example.com>".to_string())
+        );
+    }
+
+    #[test]
+    fn test_validation() {
+        assert_eq!(built("", "example.com", ""), Err(Error::LocalPartEmpty));
+        assert_eq!(built("john", "", ""), Err(Error::DomainEmpty));
+        assert_eq!(
+            built("john\u{7}", "example.com", ""),
+            Err(Error::InvalidCharacter)
+        );
+        assert_eq!(
+            built("john", "example com", ""),
+            Err(Error::InvalidCharacter)
+        );
+        assert_eq!(
+            EmailAddressBuilder::new()
+                .local_part("john")
+                .domain("example.com")
+                .display_name("John")
+                .options(Options::default().without_display_text())
+                .build(),
+            Err(Error::UnsupportedDisplayName)
+        );
+    }
+}
@@ -0,0 +1,366 @@
+/*!
+Support for producing a single canonical representation of an address.
+
+Two addresses that identify the same mailbox may be written in many ways, the domain is not case
+sensitive, the `local-part` may be quoted where quoting is unnecessary, quoted-pairs may quote
+characters that need no quoting, and Unicode text may be composed or decomposed. From RFC 5321,
+§4.1.2. [Command Argument Syntax](https://tools.ietf.org/html/rfc5321#section-4.1.2):
+
+> Systems MUST NOT define mailboxes in such a way as to require the use in SMTP of non-ASCII
+> characters (octets with the high order bit set to one) or ASCII "control characters" (decimal
+> value 0-31 and 127). ... a sending system SHOULD transmit the form that uses the minimum
+> quoting possible.
+
+From RFC 6532, §3.1. [UTF-8 Syntax and Normalization](https://tools.ietf.org/html/rfc6532#section-3.1):
+
+> ... all implementations SHOULD produce message headers in NFC form.
+*/
+
+use crate::idna::{domain_to_ascii, domain_to_unicode};
+use crate::{
+    build_address, is_dot_atom_text, quote_local_part, strip_cfws, unquote_local_part,
+    EmailAddress, Error, Host, DQUOTE,
+};
+use std::borrow::Cow;
+
+// ------------------------------------------------------------------------------------------------
+// Public Types
+// ------------------------------------------------------------------------------------------------
+
+///
+/// The form in which the labels of a Unicode domain are written by
+/// [EmailAddress::normalize](crate::EmailAddress::normalize).
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum IdnaForm {
+    /// The domain is not converted.
+    Unchanged,
+    /// All U-labels are converted to A-labels, as `EmailAddress::to_ascii_domain`; this also
+    /// lowercases the domain.
+    Ascii,
+    /// All A-labels are converted to U-labels, as `EmailAddress::to_unicode_domain`; this also
+    /// lowercases the domain.
+    Unicode,
+}
+
+///
+/// Struct of options that can be configured when normalizing with
+/// [EmailAddress::normalize](crate::EmailAddress::normalize). Comments are always removed.
+///
+#[derive(Debug, Copy, Clone)]
+pub struct NormalizeOptions {
+    ///
+    /// Specifies whether a domain name is lowercased, and an IPv4 or IPv6 address literal
+    /// written in its canonical form. Defaults to `true`.
+    ///
+    pub lowercase_domain: bool,
+
+    ///
+    /// Specifies the form of the labels of a Unicode domain. Defaults to `IdnaForm::Ascii`.
+    ///
+    pub idna_form: IdnaForm,
+
+    ///
+    /// Specifies whether the display name is removed. Defaults to `true`.
+    ///
+    pub remove_display_name: bool,
+
+    ///
+    /// Specifies whether the quotes around a `local-part` are removed if it is a valid
+    /// `dot-atom` without them. Defaults to `true`.
+    ///
+    pub remove_unnecessary_quotes: bool,
+
+    ///
+    /// Specifies whether a quoted `local-part` is written with the minimum escaping, only
+    /// the `"` and `\` characters are written as quoted-pairs. Defaults to `true`.
+    ///
+    pub minimal_escaping: bool,
+
+    ///
+    /// Specifies whether the `local-part` is normalized to Unicode Normalization Form C. This
+    /// has no effect unless the `normalization_support` feature is enabled. Defaults to `true`.
+    ///
+    pub nfc_local_part: bool,
+}
+
+// ------------------------------------------------------------------------------------------------
+// Implementations
+// ------------------------------------------------------------------------------------------------
+
+impl Default for NormalizeOptions {
+    fn default() -> Self {
+        Self {
+            lowercase_domain: true,
+            idna_form: IdnaForm::Ascii,
+            remove_display_name: true,
+            remove_unnecessary_quotes: true,
+            minimal_escaping: true,
+            nfc_local_part: true,
+        }
+    }
+}
+
+impl NormalizeOptions {
+    /// Set the value of `lowercase_domain` to `true`.
+    #[inline(always)]
+    pub const fn with_lowercase_domain(self) -> Self {
+        Self {
+            lowercase_domain: true,
+            ..self
+        }
+    }
+    /// Set the value of `lowercase_domain` to `false`.
+    #[inline(always)]
+    pub const fn without_lowercase_domain(self) -> Self {
+        Self {
+            lowercase_domain: false,
+            ..self
+        }
+    }
+    /// Set the value of `idna_form`.
+    #[inline(always)]
+    pub const fn with_idna_form(self, idna_form: IdnaForm) -> Self {
+        Self { idna_form, ..self }
+    }
+    /// Set the value of `remove_display_name` to `true`.
+    #[inline(always)]
+    pub const fn without_display_name(self) -> Self {
+        Self {
+            remove_display_name: true,
+            ..self
+        }
+    }
+    /// Set the value of `remove_display_name` to `false`.
+    #[inline(always)]
+    pub const fn with_display_name(self) -> Self {
+        Self {
+            remove_display_name: false,
+            ..self
+        }
+    }
+    /// Set the value of `remove_unnecessary_quotes` to `true`.
+    #[inline(always)]
+    pub const fn without_unnecessary_quotes(self) -> Self {
+        Self {
+            remove_unnecessary_quotes: true,
+            ..self
+        }
+    }
+    /// Set the value of `remove_unnecessary_quotes` to `false`.
+    #[inline(always)]
+    pub const fn with_unnecessary_quotes(self) -> Self {
+        Self {
+            remove_unnecessary_quotes: false,
+            ..self
+        }
+    }
+    /// Set the value of `minimal_escaping` to `true`.
+    #[inline(always)]
+    pub const fn with_minimal_escaping(self) -> Self {
+        Self {
+            minimal_escaping: true,
+            ..self
+        }
+    }
+    /// Set the value of `minimal_escaping` to `false`.
+    #[inline(always)]
+    pub const fn without_minimal_escaping(self) -> Self {
+        Self {
+            minimal_escaping: false,
+            ..self
+        }
+    }
+    /// Set the value of `nfc_local_part` to `true`.
+    #[inline(always)]
+    pub const fn with_nfc_local_part(self) -> Self {
+        Self {
+            nfc_local_part: true,
+            ..self
+        }
+    }
+    /// Set the value of `nfc_local_part` to `false`.
+    #[inline(always)]
+    pub const fn without_nfc_local_part(self) -> Self {
+        Self {
+            nfc_local_part: false,
+            ..self
+        }
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Crate Functions
+// ------------------------------------------------------------------------------------------------
+
+///
+/// Build the normalized form of `email`, which is parsed again to ensure that the result is
+/// a valid address.
+///
+pub(crate) fn normalize_address(
+    email: &EmailAddress,
+    options: NormalizeOptions,
+) -> Result<EmailAddress, Error> {
+    let local_part = normalize_local_part(email.local_part(), options);
+    let domain = normalize_domain(email, options)?;
+
+    let display = if options.remove_display_name {
+        ""
+    } else {
+        email.display_part()
+    };
+
+    build_address(display, &local_part, &domain)
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Functions
+// ------------------------------------------------------------------------------------------------
+
+fn normalize_local_part(local_part: &str, options: NormalizeOptions) -> String {
+    let raw = strip_cfws(local_part).unwrap_or(Cow::Borrowed(local_part));
+    let value = nfc(unquote_local_part(&raw).into_owned(), options);
+
+    if options.remove_unnecessary_quotes && is_dot_atom_text(&value) {
+        value
+    } else if options.minimal_escaping && raw.starts_with(DQUOTE) {
+        quote_local_part(&value)
+    } else {
+        nfc(raw.into_owned(), options)
+    }
+}
+
+fn normalize_domain(email: &EmailAddress, options: NormalizeOptions) -> Result<String, Error> {
+    let host = email.host();
+    match host {
+        Host::Domain(domain) => {
+            let domain = strip_cfws(domain).unwrap_or(Cow::Borrowed(domain));
+            let domain = match options.idna_form {
+                IdnaForm::Unchanged => domain.into_owned(),
+                IdnaForm::Ascii => domain_to_ascii(&domain)?,
+                IdnaForm::Unicode => domain_to_unicode(&domain)?,
+            };
+            if options.lowercase_domain {
+                Ok(domain.to_lowercase())
+            } else {
+                Ok(domain)
+            }
+        }
+        Host::Ipv4(_) | Host::Ipv6(_) if options.lowercase_domain => Ok(host.to_string()),
+        _ => {
+            let domain = email.domain();
+            Ok(strip_cfws(domain)
+                .unwrap_or(Cow::Borrowed(domain))
+                .into_owned())
+        }
+    }
+}
+
+fn nfc(text: String, options: NormalizeOptions) -> String {
+    if options.nfc_local_part {
+        crate::idna::normalize(text)
+    } else {
+        text
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Unit Tests
+// ------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Options;
+
+    fn normalized(address: &str, options: NormalizeOptions) -> String {
+        EmailAddress::parse_with_options(address, Options::default().with_comments())
+            .unwrap()
+            .normalize(options)
+            .unwrap()
+            .to_string()
+    }
+
+    #[test]
+    fn test_canonical() {
+        let options = NormalizeOptions::default();
+
+        assert_eq!(
+            normalized("simon@Example.COM", options),
+            "simon@example.com"
+        );
+        assert_eq!(
+            normalized("Simon <simon@example.com>", options),
+            "simon@example.com"
+        );
+        assert_eq!(
+            normalized("\"simon\"@example.com", options),
+            "simon@example.com"
+        );
+        assert_eq!(
+            normalized("\"si\\mon\"@example.com", options),
+            "simon@example.com"
+        );
+        assert_eq!(
+            normalized("\"s\\i m\\\"on\"@example.com", options),
+            "\"si m\\\"on\"@example.com"
+        );
+        assert_eq!(
+            normalized("simon(work)@example.com (home)", options),
+            "simon@example.com"
+        );
+        assert_eq!(
+            normalized("用户@例子.广告", options),
+            "用户@xn--fsqu00a.xn--4rr70v"
+        );
+        assert_eq!(
+            normalized("simon@[IPv6:2001:DB8:0:0:0:0:0:1]", options),
+            "simon@[IPv6:2001:db8::1]"
+        );
+    }
+
+    #[test]
+    fn test_configured_steps() {
+        let options = NormalizeOptions::default()
+            .without_lowercase_domain()
+            .with_idna_form(IdnaForm::Unchanged)
+            .with_display_name()
+            .with_unnecessary_quotes()
+            .without_minimal_escaping();
+
+        assert_eq!(
+            normalized("Simon <\"s\\imon\"@Sörensen.Example>", options),
+            "Simon <\"s\\imon\"@Sörensen.Example>"
+        );
+        assert_eq!(
+            normalized(
+                "Simon <simon@xn--srensen-90a.example>",
+                options.with_idna_form(IdnaForm::Unicode)
+            ),
+            "Simon <simon@sörensen.example>"
+        );
+        assert_eq!(
+            normalized(
+                "simon@[IPv6:2001:DB8:0:0:0:0:0:1]",
+                options.with_lowercase_domain()
+            ),
+            "simon@[IPv6:2001:db8::1]"
+        );
+    }
+
+    #[cfg(feature = "normalization_support")]
+    #[test]
+    fn test_nfc_local_part() {
+        assert_eq!(
+            normalized("So\u{0308}ren@example.com", NormalizeOptions::default()),
+            "S\u{00F6}ren@example.com"
+        );
+        assert_eq!(
+            normalized(
+                "So\u{0308}ren@example.com",
+                NormalizeOptions::default().without_nfc_local_part()
+            ),
+            "So\u{0308}ren@example.com"
+        );
+    }
+}
@@ -0,0 +1,456 @@
+/*!
+Support for reporting every problem found in an address, rather than only the first.
+
+[EmailAddress::check_all](crate::EmailAddress::check_all) continues past the first failed check,
+so that, for example, a `local-part` that is too long is reported along with an empty
+`sub-domain` in the `domain`. Each [Finding] carries a [ParseError] giving the location of the
+problem, and a [Severity] which distinguishes syntax errors from addresses that are well-formed
+but not allowed by the [Options] used.
+
+The checks stop early only where the structure of the address cannot be determined, that is when
+the `@` separator or the closing `>` is missing.
+
+For a valid address [EmailAddress::diagnose](crate::EmailAddress::diagnose) returns a
+[Diagnosis], following the categories used by the [isemail](https://github.com/dominicsayers/isemail)
+library, which distinguishes an address usable in the SMTP envelope from one which is only valid
+under the broader RFC 5322 message syntax.
+*/
+
+use crate::parse_error::locate;
+use crate::{
+    parse_address_with, strip_cfws, EmailAddress, Error, Host, Options, ParseError, DOT, DQUOTE,
+    HTAB,
+};
+use std::borrow::Cow;
+use std::fmt::{Display, Formatter};
+
+const ADDRESS_MAX_LENGTH: usize = 254;
+
+// ------------------------------------------------------------------------------------------------
+// Public Types
+// ------------------------------------------------------------------------------------------------
+
+///
+/// The severity of a [Finding].
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Severity {
+    /// The address is well-formed, but not allowed by the [Options] used; for example it has a
+    /// display name where these are disallowed, or too few `sub-domain`s.
+    Warning,
+    /// The address is not well-formed.
+    Error,
+}
+
+///
+/// The category of a valid address, from those used by the `isemail` library. The categories are
+/// ordered from most to least widely accepted, so that a policy may be applied by comparison.
+///
+/// ```rust
+/// use email_address::*;
+/// use std::str::FromStr;
+///
+/// let email = EmailAddress::from_str("\"simon johnston\"@example.com").unwrap();
+///
+/// assert_eq!(email.diagnose(), Diagnosis::Rfc5321);
+/// assert!(email.diagnose() <= Diagnosis::Rfc5321);
+/// ```
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Diagnosis {
+    /// The address is valid, and suitable for use in both the SMTP envelope and message headers.
+    Valid,
+    /// The address is valid for use in the SMTP envelope, but contains unusual elements which
+    /// may be rejected by some systems; a quoted `local-part`, an IPv4 or IPv6 address literal,
+    /// or a domain consisting only of a top-level domain.
+    Rfc5321,
+    /// The address contains deprecated elements, comments or folding white space (`CFWS`), and
+    /// must be modified before use in the SMTP envelope.
+    Deprecated,
+    /// The address is only valid under the broad RFC 5322 syntax and cannot be used in the SMTP
+    /// envelope; for example it uses a general address literal, a tab within a quoted
+    /// `local-part`, or is longer than 254 characters.
+    Rfc5322,
+}
+
+///
+/// A single problem found in an address by [EmailAddress::check_all](crate::EmailAddress::check_all).
+///
+/// ```rust
+/// use email_address::*;
+///
+/// let findings = EmailAddress::check_all(
+///     "Simon <simon@localhost>",
+///     Options::default().without_display_text().with_minimum_sub_domains(2),
+/// );
+///
+/// assert_eq!(findings.len(), 2);
+/// assert_eq!(findings[0].severity, Severity::Warning);
+/// assert_eq!(findings[0].error.kind, Error::UnsupportedDisplayName);
+/// assert_eq!(findings[1].severity, Severity::Warning);
+/// assert_eq!(findings[1].error.kind, Error::DomainTooFew);
+/// ```
+///
+#[derive(Debug, Clone, PartialEq)]
+pub struct Finding {
+    /// Whether the address is malformed, or only disallowed by the [Options] used.
+    pub severity: Severity,
+    /// The kind and location of the problem.
+    pub error: ParseError,
+}
+
+// ------------------------------------------------------------------------------------------------
+// Implementations
+// ------------------------------------------------------------------------------------------------
+
+impl Display for Diagnosis {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Diagnosis::Valid => write!(f, "valid"),
+            Diagnosis::Rfc5321 => write!(f, "valid for SMTP, with unusual elements"),
+            Diagnosis::Deprecated => write!(f, "valid, with deprecated elements"),
+            Diagnosis::Rfc5322 => write!(f, "valid only under RFC 5322"),
+        }
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl Display for Severity {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Severity::Warning => write!(f, "warning"),
+            Severity::Error => write!(f, "error"),
+        }
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl Display for Finding {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.severity, self.error)
+    }
+}
+
+impl From<ParseError> for Finding {
+    fn from(error: ParseError) -> Self {
+        let severity = match error.kind {
+            Error::UnsupportedDisplayName
+            | Error::UnsupportedDomainLiteral
+            | Error::DomainTooFew
+            | Error::RequiresSmtpUtf8 => Severity::Warning,
+            _ => Severity::Error,
+        };
+        Self { severity, error }
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Crate Functions
+// ------------------------------------------------------------------------------------------------
+
+///
+/// Run every check on `address`, returning the findings in the order of the text they concern.
+/// This uses the same parser as `parse_with_options`, which is asked to continue past each error
+/// rather than stop at the first, so the address is valid if, and only if, no findings are
+/// returned.
+///
+pub(crate) fn check_address(address: &str, options: Options) -> Vec<Finding> {
+    let mut findings = Vec::new();
+    let _ = parse_address_with(address, options, &mut |kind, component, subject| {
+        findings.push(locate(address, subject, component, options, kind).into());
+        Ok(())
+    });
+    findings
+}
+
+///
+/// Classify a valid address, returning the least widely accepted category of any of its elements.
+///
+pub(crate) fn diagnose_address(email: &EmailAddress) -> Diagnosis {
+    let local_part = email.local_part();
+    let domain = email.domain();
+    let stripped_local_part = strip_cfws(local_part).unwrap_or(Cow::Borrowed(local_part));
+    let stripped_domain = strip_cfws(domain).unwrap_or(Cow::Borrowed(domain));
+
+    let mut diagnosis = Diagnosis::Valid;
+    let mut raise = |to: Diagnosis| diagnosis = diagnosis.max(to);
+
+    // RFC 5321 §4.1.2 does not allow HTAB within a `Quoted-string`, either as `qtextSMTP` or
+    // within a `quoted-pairSMTP`.
+    if stripped_local_part.starts_with(DQUOTE) {
+        raise(if stripped_local_part.contains(HTAB) {
+            Diagnosis::Rfc5322
+        } else {
+            Diagnosis::Rfc5321
+        });
+    }
+
+    match email.host() {
+        Host::Domain(_) => {
+            let mut labels = stripped_domain.split(DOT);
+            let tld = labels.next_back().unwrap_or_default();
+            if labels.next().is_none() || tld.chars().all(|c| c.is_ascii_digit()) {
+                raise(Diagnosis::Rfc5321);
+            }
+        }
+        Host::Ipv4(_) | Host::Ipv6(_) => raise(Diagnosis::Rfc5321),
+        Host::General { .. } => raise(Diagnosis::Rfc5322),
+    }
+
+    if stripped_local_part != local_part || stripped_domain != domain {
+        raise(Diagnosis::Deprecated);
+    }
+
+    // RFC 5321 §4.5.3.1.3 limits a `Path` to 256 octets, including the angle brackets.
+    if stripped_local_part.len() + stripped_domain.len() + 1 > ADDRESS_MAX_LENGTH {
+        raise(Diagnosis::Rfc5322);
+    }
+
+    diagnosis
+}
+
+// ------------------------------------------------------------------------------------------------
+// Unit Tests
+// ------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Component, EmailAddress, LOCAL_PART_MAX_LENGTH};
+
+    fn kinds(address: &str, options: Options) -> Vec<(Severity, Error, Component)> {
+        EmailAddress::check_all(address, options)
+            .into_iter()
+            .map(|finding| {
+                (
+                    finding.severity,
+                    finding.error.kind,
+                    finding.error.component,
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_valid_has_no_findings() {
+        assert!(EmailAddress::check_all("simon@example.com", Options::default()).is_empty());
+        assert!(EmailAddress::check_all(
+            "Simon <simon(work)@example.com>",
+            Options::default().with_comments()
+        )
+        .is_empty());
+    }
+
+    #[test]
+    fn test_multiple_findings() {
+        let local_part = "a".repeat(LOCAL_PART_MAX_LENGTH + 1);
+        let address = format!("{}@example..c_m.-com", local_part);
+
+        assert_eq!(
+            kinds(&address, Options::default()),
+            vec![
+                (
+                    Severity::Error,
+                    Error::LocalPartTooLong,
+                    Component::LocalPart
+                ),
+                (
+                    Severity::Error,
+                    Error::SubDomainEmpty,
+                    Component::DomainLabel(1)
+                ),
+                (
+                    Severity::Error,
+                    Error::InvalidCharacter,
+                    Component::DomainLabel(3)
+                ),
+            ]
+        );
+
+        let findings = EmailAddress::check_all(&address, Options::default());
+        assert_eq!(findings[0].error.span, 0..65);
+        assert_eq!(findings[1].error.span, 74..74);
+        assert_eq!(findings[2].error.span, 79..80);
+        assert_eq!(findings[2].error.found, Some('-'));
+    }
+
+    #[test]
+    fn test_local_part_and_domain_findings() {
+        assert_eq!(
+            kinds("sim,on@[127.0.0.256]", Options::default()),
+            vec![
+                (
+                    Severity::Error,
+                    Error::InvalidCharacter,
+                    Component::LocalPart
+                ),
+                (Severity::Error, Error::InvalidIPAddress, Component::Domain),
+            ]
+        );
+        assert_eq!(
+            kinds(
+                "Dörte <dörte@[127.0.0.1]>",
+                Options::default()
+                    .without_display_text()
+                    .without_domain_literal()
+                    .without_smtputf8()
+            ),
+            vec![
+                (
+                    Severity::Warning,
+                    Error::UnsupportedDisplayName,
+                    Component::DisplayName
+                ),
+                (
+                    Severity::Warning,
+                    Error::RequiresSmtpUtf8,
+                    Component::LocalPart
+                ),
+                (
+                    Severity::Warning,
+                    Error::UnsupportedDomainLiteral,
+                    Component::Domain
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_structural_findings_stop() {
+        assert_eq!(
+            kinds("simon.example.com", Options::default()),
+            vec![(Severity::Error, Error::MissingSeparator, Component::Address)]
+        );
+        assert_eq!(
+            kinds("Simon <si,mon@exa,mple.com", Options::default()),
+            vec![(
+                Severity::Error,
+                Error::MissingEndBracket,
+                Component::Address
+            )]
+        );
+    }
+
+    fn diagnose(address: &str) -> Diagnosis {
+        EmailAddress::parse_with_options(
+            address,
+            Options::default().with_comments().with_general_literal(),
+        )
+        .unwrap()
+        .diagnose()
+    }
+
+    #[test]
+    fn test_diagnose() {
+        assert_eq!(diagnose("simon@example.com"), Diagnosis::Valid);
+        assert_eq!(diagnose("Simon <simon@example.com>"), Diagnosis::Valid);
+        assert_eq!(diagnose("dörte@sörensen.example.com"), Diagnosis::Valid);
+
+        assert_eq!(diagnose("\"simon\"@example.com"), Diagnosis::Rfc5321);
+        assert_eq!(diagnose("simon@[127.0.0.1]"), Diagnosis::Rfc5321);
+        assert_eq!(diagnose("simon@[IPv6:::1]"), Diagnosis::Rfc5321);
+        assert_eq!(diagnose("simon@localhost"), Diagnosis::Rfc5321);
+        assert_eq!(diagnose("simon@example.123"), Diagnosis::Rfc5321);
+
+        assert_eq!(diagnose("simon(work)@example.com"), Diagnosis::Deprecated);
+        assert_eq!(diagnose("simon@example.com (Simon)"), Diagnosis::Deprecated);
+        assert_eq!(diagnose("\"simon\"@ example.com"), Diagnosis::Deprecated);
+
+        assert_eq!(diagnose("\"sim\ton\"@example.com"), Diagnosis::Rfc5322);
+        assert_eq!(diagnose("simon@[x400:c=gb]"), Diagnosis::Rfc5322);
+        assert_eq!(
+            diagnose(&format!(
+                "{}@{}.com",
+                "s".repeat(64),
+                vec!["a".repeat(60); 4].join(".")
+            )),
+            Diagnosis::Rfc5322
+        );
+    }
+
+    #[test]
+    fn test_parse_stops_at_first_finding() {
+        let without_display = Options::default().without_display_text();
+        for (address, options, error, findings) in [
+            (
+                "@",
+                Options::default(),
+                Error::LocalPartEmpty,
+                vec![
+                    (Error::LocalPartEmpty, Component::LocalPart),
+                    (Error::DomainEmpty, Component::Domain),
+                ],
+            ),
+            (
+                "sim..on@-example.com",
+                Options::default(),
+                Error::InvalidCharacter,
+                vec![
+                    (Error::InvalidCharacter, Component::LocalPart),
+                    (Error::InvalidCharacter, Component::DomainLabel(0)),
+                ],
+            ),
+            (
+                "\"simon@example.com",
+                Options::default(),
+                Error::InvalidCharacter,
+                vec![(Error::InvalidCharacter, Component::LocalPart)],
+            ),
+            (
+                "simon@[IPv6:::1:2:3:4:5:6:7:8]",
+                Options::default(),
+                Error::InvalidIPAddress,
+                vec![(Error::InvalidIPAddress, Component::Domain)],
+            ),
+            (
+                "simon@xn--zz.example.com",
+                Options::default(),
+                Error::InvalidALabel,
+                vec![(Error::InvalidALabel, Component::DomainLabel(0))],
+            ),
+            (
+                "<simon@example.com>",
+                Options::default(),
+                Error::MissingDisplayName,
+                vec![(Error::MissingDisplayName, Component::DisplayName)],
+            ),
+            (
+                "Simon <sim on@exa mple>",
+                without_display,
+                Error::UnsupportedDisplayName,
+                vec![
+                    (Error::UnsupportedDisplayName, Component::DisplayName),
+                    (Error::InvalidCharacter, Component::LocalPart),
+                    (Error::InvalidCharacter, Component::DomainLabel(0)),
+                ],
+            ),
+            (
+                "sim on(work)@ exa mple.com",
+                Options::default().with_comments(),
+                Error::InvalidCharacter,
+                vec![
+                    (Error::InvalidCharacter, Component::LocalPart),
+                    (Error::InvalidCharacter, Component::Domain),
+                ],
+            ),
+        ] {
+            assert_eq!(
+                EmailAddress::parse_with_options(address, options),
+                Err(error),
+                "{:?}",
+                address
+            );
+            assert_eq!(
+                EmailAddress::check_all(address, options)
+                    .into_iter()
+                    .map(|finding| (finding.error.kind, finding.error.component))
+                    .collect::<Vec<_>>(),
+                findings,
+                "{:?}",
+                address
+            );
+        }
+    }
+}
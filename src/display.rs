@@ -0,0 +1,552 @@
+/*!
+Support for the display name that precedes an `angle-addr`, parsed and formatted as an RFC 5322
+`phrase` which may contain RFC 2047 encoded-words.
+
+From RFC 5322, §3.2.5. [Miscellaneous Tokens](https://tools.ietf.org/html/rfc5322#section-3.2.5)
+and §3.4. [Address Specification](https://tools.ietf.org/html/rfc5322#section-3.4):
+
+```ebnf
+word            =   atom / quoted-string
+
+phrase          =   1*word / obs-phrase
+
+obs-phrase      =   word *(word / "." / CFWS)
+
+display-name    =   phrase
+```
+
+From RFC 2047, §2. [Syntax of encoded-words](https://tools.ietf.org/html/rfc2047#section-2):
+
+```ebnf
+encoded-word    =   "=?" charset "?" encoding "?" encoded-text "?="
+
+charset         =   token    ; see section 3
+
+encoding        =   token    ; see section 4
+
+encoded-text    =   1*<Any printable ASCII character other than "?"
+                       or SPACE>
+```
+*/
+
+use crate::{is_atext, is_atom, is_qtext_char, is_vchar, is_wsp, DOT, DQUOTE, ESC, LPAREN, RPAREN};
+use std::borrow::Cow;
+
+// ------------------------------------------------------------------------------------------------
+// Implementations
+// ------------------------------------------------------------------------------------------------
+
+const ENCODED_WORD_START: &str = "=?";
+const ENCODED_WORD_END: &str = "?=";
+const ENCODED_WORD_SEP: char = '?';
+const CHARSET_LANGUAGE_SEP: char = '*';
+
+const ENCODED_WORD_MAX_LENGTH: usize = 75;
+const ENCODED_WORD_UTF8_B: &str = "=?UTF-8?B?";
+const ENCODED_WORD_UTF8_Q: &str = "=?UTF-8?Q?";
+
+const BASE64_ALPHABET: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+const Q_ESCAPE: char = '=';
+const Q_SPACE: char = '_';
+
+// ------------------------------------------------------------------------------------------------
+// Crate Functions
+// ------------------------------------------------------------------------------------------------
+
+///
+/// Decode the raw display part of an address into the display name it represents. Quoted
+/// strings are unquoted, quoted-pairs resolved, comments removed, runs of white space replaced
+/// with a single space, and encoded-words decoded. White space between adjacent encoded-words
+/// is removed, as required by RFC 2047. Anything that cannot be decoded is retained as-is.
+///
+pub(crate) fn decode_display_name(raw: &str) -> Cow<'_, str> {
+    if !raw.contains(|c: char| {
+        c == DQUOTE || c == ESC || c == LPAREN || c == Q_ESCAPE || (c.is_whitespace() && c != ' ')
+    }) && !raw.contains("  ")
+    {
+        return Cow::Borrowed(raw);
+    }
+
+    let mut result = String::with_capacity(raw.len());
+    let mut pending_space = false;
+    let mut last_was_encoded = false;
+    let mut char_iter = raw.char_indices().peekable();
+    while let Some((start, c)) = char_iter.next() {
+        if c.is_whitespace() {
+            pending_space = true;
+        } else if c == LPAREN {
+            skip_comment(&mut char_iter);
+            pending_space = true;
+        } else if c == DQUOTE {
+            if pending_space && !result.is_empty() {
+                result.push(' ');
+            }
+            while let Some((_, c)) = char_iter.next() {
+                match c {
+                    DQUOTE => break,
+                    ESC => {
+                        if let Some((_, c)) = char_iter.next() {
+                            result.push(c);
+                        }
+                    }
+                    // unfold any folded lines within the quoted string.
+                    '\r' | '\n' => (),
+                    _ => result.push(c),
+                }
+            }
+            pending_space = false;
+            last_was_encoded = false;
+        } else {
+            let mut end = raw.len();
+            while let Some((index, c)) = char_iter.peek() {
+                if c.is_whitespace() || *c == DQUOTE || *c == LPAREN {
+                    end = *index;
+                    break;
+                }
+                let _ = char_iter.next();
+            }
+            let word = &raw[start..end];
+            match decode_encoded_word(word) {
+                Some(decoded) => {
+                    if pending_space && !last_was_encoded && !result.is_empty() {
+                        result.push(' ');
+                    }
+                    result.push_str(&decoded);
+                    last_was_encoded = true;
+                }
+                None => {
+                    if pending_space && !result.is_empty() {
+                        result.push(' ');
+                    }
+                    result.push_str(word);
+                    last_was_encoded = false;
+                }
+            }
+            pending_space = false;
+        }
+    }
+    Cow::Owned(result)
+}
+
+///
+/// Returns `true` if `raw` is an RFC 5322 `phrase`, one or more atoms or quoted strings with
+/// any CFWS between them; as in the obsolete syntax a `.` is also allowed after the first word.
+///
+pub(crate) fn is_phrase(raw: &str) -> bool {
+    let mut words = 0;
+    let mut char_iter = raw.char_indices();
+    while let Some((_, c)) = char_iter.next() {
+        if c == LPAREN {
+            if !skip_comment(&mut char_iter) {
+                return false;
+            }
+        } else if c == DQUOTE {
+            loop {
+                match char_iter.next() {
+                    Some((_, DQUOTE)) => break,
+                    Some((_, ESC)) => match char_iter.next() {
+                        Some((_, c)) if is_vchar(c) || is_wsp(c) => (),
+                        _ => return false,
+                    },
+                    Some((_, c)) if is_qtext_char(c) || c.is_whitespace() => (),
+                    _ => return false,
+                }
+            }
+            words += 1;
+        } else if is_atext(c) {
+            words += 1;
+        } else if !(c.is_whitespace() || (c == DOT && words > 0)) {
+            return false;
+        }
+    }
+    words > 0
+}
+
+///
+/// Encode a display name so that it may be safely written before an `angle-addr` in a header.
+/// If `encode_non_ascii` is set, and the name contains non-ASCII characters, it is written as
+/// a sequence of RFC 2047 encoded-words; otherwise, if it is not a sequence of atoms separated
+/// by single spaces, it is written as a quoted string.
+///
+pub(crate) fn encode_display_name(name: &str, encode_non_ascii: bool) -> Cow<'_, str> {
+    let name: Cow<'_, str> = if name.contains(char::is_control) {
+        Cow::Owned(
+            name.chars()
+                .map(|c| if c.is_control() { ' ' } else { c })
+                .collect(),
+        )
+    } else {
+        Cow::Borrowed(name)
+    };
+
+    if encode_non_ascii && !name.is_ascii() {
+        Cow::Owned(encode_words(&name))
+    } else if name.is_empty()
+        || (name.split(' ').all(is_atom) && !name.contains(ENCODED_WORD_START))
+    {
+        name
+    } else {
+        let mut quoted = String::with_capacity(name.len() + 2);
+        quoted.push(DQUOTE);
+        for c in name.chars() {
+            if c == DQUOTE || c == ESC {
+                quoted.push(ESC);
+            }
+            quoted.push(c);
+        }
+        quoted.push(DQUOTE);
+        Cow::Owned(quoted)
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Functions
+// ------------------------------------------------------------------------------------------------
+
+///
+/// Encode `text` as UTF-8 encoded-words, using whichever of the B and Q encodings is shorter,
+/// and splitting into multiple words separated by a space so that no word exceeds the maximum
+/// length of 75 characters.
+///
+fn encode_words(text: &str) -> String {
+    let q_length: usize = text.chars().map(q_encoded_length).sum();
+    let use_q = q_length <= base64_length(text.len());
+
+    let max_length = ENCODED_WORD_MAX_LENGTH - ENCODED_WORD_UTF8_B.len() - ENCODED_WORD_END.len();
+    let mut words: Vec<String> = Vec::default();
+    let mut start = 0;
+    let mut q_length = 0;
+    for (index, c) in text.char_indices() {
+        let length = if use_q {
+            q_length + q_encoded_length(c)
+        } else {
+            base64_length(index + c.len_utf8() - start)
+        };
+        if length > max_length {
+            words.push(encode_word(&text[start..index], use_q));
+            start = index;
+            q_length = q_encoded_length(c);
+        } else {
+            q_length += q_encoded_length(c);
+        }
+    }
+    words.push(encode_word(&text[start..], use_q));
+    words.join(" ")
+}
+
+fn encode_word(text: &str, use_q: bool) -> String {
+    if use_q {
+        let mut word = String::from(ENCODED_WORD_UTF8_Q);
+        for b in text.bytes() {
+            if b == b' ' {
+                word.push(Q_SPACE);
+            } else if is_q_safe(b) {
+                word.push(char::from(b));
+            } else {
+                word.push_str(&format!("{}{:02X}", Q_ESCAPE, b));
+            }
+        }
+        word.push_str(ENCODED_WORD_END);
+        word
+    } else {
+        format!(
+            "{}{}{}",
+            ENCODED_WORD_UTF8_B,
+            encode_base64(text.as_bytes()),
+            ENCODED_WORD_END
+        )
+    }
+}
+
+///
+/// Characters that may appear unencoded in a Q-encoded word within a `phrase`, see
+/// RFC 2047, §5. (3).
+///
+fn is_q_safe(b: u8) -> bool {
+    b.is_ascii_alphanumeric() || matches!(b, b'!' | b'*' | b'+' | b'-' | b'/')
+}
+
+fn q_encoded_length(c: char) -> usize {
+    if c == ' ' || (c.is_ascii() && is_q_safe(c as u8)) {
+        1
+    } else {
+        c.len_utf8() * 3
+    }
+}
+
+// `usize::div_ceil` requires Rust 1.73.
+#[allow(clippy::manual_div_ceil)]
+fn base64_length(byte_length: usize) -> usize {
+    (byte_length + 2) / 3 * 4
+}
+
+fn encode_base64(bytes: &[u8]) -> String {
+    let mut encoded = String::with_capacity(base64_length(bytes.len()));
+    for chunk in bytes.chunks(3) {
+        let buffer = chunk.iter().enumerate().fold(0_u32, |buffer, (i, b)| {
+            buffer | u32::from(*b) << (16 - i * 8)
+        });
+        for i in 0..4 {
+            if i <= chunk.len() {
+                let value = (buffer >> (18 - i * 6)) & 0x3F;
+                encoded.push(char::from(BASE64_ALPHABET[value as usize]));
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+    encoded
+}
+
+///
+/// Skip the remainder of a comment, returning `false` if it is not closed.
+///
+fn skip_comment<I>(char_iter: &mut I) -> bool
+where
+    I: Iterator<Item = (usize, char)>,
+{
+    let mut depth = 1;
+    while let Some((_, c)) = char_iter.next() {
+        match c {
+            LPAREN => depth += 1,
+            RPAREN => {
+                depth -= 1;
+                if depth == 0 {
+                    return true;
+                }
+            }
+            ESC => {
+                let _ = char_iter.next();
+            }
+            _ => (),
+        }
+    }
+    false
+}
+
+///
+/// Decode a single RFC 2047 encoded-word, returning `None` if `word` is not an encoded-word
+/// or uses an unsupported charset or encoding.
+///
+fn decode_encoded_word(word: &str) -> Option<String> {
+    let inner = word
+        .strip_prefix(ENCODED_WORD_START)?
+        .strip_suffix(ENCODED_WORD_END)?;
+    let mut parts = inner.splitn(3, ENCODED_WORD_SEP);
+    let charset = parts.next()?;
+    let encoding = parts.next()?;
+    let text = parts.next()?;
+    if text.contains(|c: char| c == ENCODED_WORD_SEP || is_wsp(c) || !c.is_ascii()) {
+        return None;
+    }
+
+    // RFC 2231 allows a language to be appended to the charset.
+    let charset = match charset.split_once(CHARSET_LANGUAGE_SEP) {
+        Some((charset, _)) => charset,
+        None => charset,
+    };
+
+    let bytes = if encoding.eq_ignore_ascii_case("B") {
+        decode_base64(text)?
+    } else if encoding.eq_ignore_ascii_case("Q") {
+        decode_q(text)?
+    } else {
+        return None;
+    };
+
+    decode_charset(charset, bytes)
+}
+
+fn decode_charset(charset: &str, bytes: Vec<u8>) -> Option<String> {
+    let charset = charset.to_ascii_lowercase();
+    match charset.as_str() {
+        "utf-8" | "utf8" => String::from_utf8(bytes).ok(),
+        "us-ascii" | "ascii" => {
+            if bytes.is_ascii() {
+                String::from_utf8(bytes).ok()
+            } else {
+                None
+            }
+        }
+        "iso-8859-1" | "iso_8859-1" | "iso8859-1" | "latin1" | "latin-1" | "l1" => {
+            Some(bytes.into_iter().map(char::from).collect())
+        }
+        _ => None,
+    }
+}
+
+fn decode_q(text: &str) -> Option<Vec<u8>> {
+    let mut bytes = Vec::with_capacity(text.len());
+    let mut byte_iter = text.bytes();
+    while let Some(b) = byte_iter.next() {
+        if b == Q_ESCAPE as u8 {
+            let high = hex_value(byte_iter.next()?)?;
+            let low = hex_value(byte_iter.next()?)?;
+            bytes.push(high << 4 | low);
+        } else if b == Q_SPACE as u8 {
+            bytes.push(b' ');
+        } else {
+            bytes.push(b);
+        }
+    }
+    Some(bytes)
+}
+
+fn hex_value(b: u8) -> Option<u8> {
+    char::from(b).to_digit(16).map(|d| d as u8)
+}
+
+fn decode_base64(text: &str) -> Option<Vec<u8>> {
+    let mut bytes = Vec::with_capacity(text.len() * 3 / 4);
+    let mut buffer = 0_u32;
+    let mut bits = 0;
+    for b in text.trim_end_matches('=').bytes() {
+        let value = BASE64_ALPHABET.iter().position(|a| *a == b)? as u8;
+        buffer = (buffer << 6) | u32::from(value);
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            bytes.push((buffer >> bits) as u8);
+            buffer &= (1 << bits) - 1;
+        }
+    }
+    Some(bytes)
+}
+
+// ------------------------------------------------------------------------------------------------
+// Unit Tests
+// ------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn decodes_to(raw: &str, expected: &str) {
+        println!(">> <{}>, expecting <{}>", raw, expected);
+        assert_eq!(decode_display_name(raw), expected);
+    }
+
+    #[test]
+    fn test_plain_phrase() {
+        decodes_to("Simon Johnston", "Simon Johnston");
+        decodes_to("John Q. Public", "John Q. Public");
+        assert!(matches!(decode_display_name("Simon"), Cow::Borrowed(_)));
+    }
+
+    #[test]
+    fn test_quoted_phrase() {
+        decodes_to("\"Doe, John\"", "Doe, John");
+        decodes_to("\"John \\\"Jack\\\" Doe\"", "John \"Jack\" Doe");
+        decodes_to("\"Joe\" \"Q\\\\Public\"", "Joe Q\\Public");
+    }
+
+    #[test]
+    fn test_white_space_and_comments() {
+        decodes_to("Simon\r\n   Johnston", "Simon Johnston");
+        decodes_to("Simon (the author) Johnston", "Simon Johnston");
+        decodes_to(
+            "Simon (nested (comment \\) here)) Johnston",
+            "Simon Johnston",
+        );
+    }
+
+    #[test]
+    fn test_encoded_words_b() {
+        decodes_to("=?UTF-8?B?S8O2bmln?=", "König");
+        decodes_to("=?utf-8?b?55So5oi3?= Smith", "用户 Smith");
+        decodes_to("=?ISO-8859-1?B?SvZyZw==?=", "Jörg");
+    }
+
+    #[test]
+    fn test_encoded_words_q() {
+        decodes_to(
+            "=?ISO-8859-1?Q?Keld_J=F8rn_Simonsen?=",
+            "Keld Jørn Simonsen",
+        );
+        decodes_to("=?UTF-8?Q?Andr=C3=A9?= Pirard", "André Pirard");
+        decodes_to("=?US-ASCII*EN?Q?Keith_Moore?=", "Keith Moore");
+    }
+
+    #[test]
+    fn test_adjacent_encoded_words() {
+        decodes_to("=?ISO-8859-1?Q?a?= =?ISO-8859-1?Q?b?=", "ab");
+        decodes_to("=?ISO-8859-1?Q?a?=\r\n  =?ISO-8859-1?Q?b?=", "ab");
+        decodes_to("=?ISO-8859-1?Q?a_?= =?ISO-8859-1?Q?b?=", "a b");
+        decodes_to("=?ISO-8859-1?Q?a?= b", "a b");
+    }
+
+    fn encodes_to(name: &str, encode_non_ascii: bool, expected: &str) {
+        println!(">> <{}>, expecting <{}>", name, expected);
+        let encoded = encode_display_name(name, encode_non_ascii);
+        assert_eq!(encoded, expected);
+        assert_eq!(
+            decode_display_name(&encoded),
+            name.replace(char::is_control, " ")
+        );
+    }
+
+    #[test]
+    fn test_encode_atoms() {
+        encodes_to("Simon Johnston", false, "Simon Johnston");
+        encodes_to("коля", false, "коля");
+        assert!(matches!(
+            encode_display_name("Simon", false),
+            Cow::Borrowed(_)
+        ));
+    }
+
+    #[test]
+    fn test_encode_quoted() {
+        encodes_to("Doe, John", false, "\"Doe, John\"");
+        encodes_to("John Q. Public", false, "\"John Q. Public\"");
+        encodes_to("<john@example.com>", false, "\"<john@example.com>\"");
+        encodes_to("John \"Jack\" Doe", false, "\"John \\\"Jack\\\" Doe\"");
+        encodes_to("C:\\Users", false, "\"C:\\\\Users\"");
+        encodes_to(" Simon  Johnston", false, "\" Simon  Johnston\"");
+        encodes_to("=?UTF-8?Q?a?=", false, "\"=?UTF-8?Q?a?=\"");
+    }
+
+    #[test]
+    fn test_encode_control_characters() {
+        assert_eq!(
+            encode_display_name("Simon\r\nBcc: all@example.com", false),
+            "\"Simon  Bcc: all@example.com\""
+        );
+    }
+
+    #[test]
+    fn test_encode_words() {
+        encodes_to("König", true, "=?UTF-8?B?S8O2bmln?=");
+        encodes_to("André Pirard", true, "=?UTF-8?Q?Andr=C3=A9_Pirard?=");
+        encodes_to("用户", true, "=?UTF-8?B?55So5oi3?=");
+        encodes_to("Simon, Johnston", true, "\"Simon, Johnston\"");
+    }
+
+    #[test]
+    fn test_encode_long_words() {
+        let name = "用户".repeat(20);
+        let encoded = encode_display_name(&name, true);
+
+        assert!(encoded.split(' ').count() > 1);
+        assert!(encoded.split(' ').all(|word| word.len() <= 75));
+        assert_eq!(decode_display_name(&encoded), name);
+
+        let name = "Jörg ".repeat(20);
+        let encoded = encode_display_name(name.trim_end(), true);
+
+        assert!(encoded.split(' ').count() > 1);
+        assert!(encoded.split(' ').all(|word| word.len() <= 75));
+        assert_eq!(decode_display_name(&encoded), name.trim_end());
+    }
+
+    #[test]
+    fn test_undecodable_words() {
+        decodes_to("=?x-unknown?Q?a?=", "=?x-unknown?Q?a?=");
+        decodes_to("=?UTF-8?X?a?=", "=?UTF-8?X?a?=");
+        decodes_to("=?UTF-8?B?!!!?=", "=?UTF-8?B?!!!?=");
+        decodes_to("=?US-ASCII?Q?=FF?=", "=?US-ASCII?Q?=FF?=");
+        decodes_to("\"=?UTF-8?B?S8O2bmln?=\"", "=?UTF-8?B?S8O2bmln?=");
+    }
+}
@@ -0,0 +1,486 @@
+/*!
+The productions of the address grammar shared by the runtime parser and the compile-time
+validation of the [email!](crate::email!) macro.
+
+These are all `const fn`s taking byte offsets into the text, as slicing a string is not possible
+in a `const fn`. Matching bytes rather than characters is sufficient as every byte of a non-ASCII
+character is >= 0x80, and the only non-ASCII characters that are treated specially are white
+space, which `trim` decodes, and the letters and digits at the boundaries of domain labels, which
+are left to the caller as they depend on Unicode tables.
+*/
+
+use crate::{Error, SUB_DOMAIN_MAX_LENGTH};
+
+// ------------------------------------------------------------------------------------------------
+// Crate Functions
+// ------------------------------------------------------------------------------------------------
+
+///
+/// Split the text into a display name and an `addr-spec`, returning the bounds of each. If
+/// there is no `angle-addr` following a display name the display name is empty and the
+/// `addr-spec` is the whole text.
+///
+pub(crate) const fn split_display_email(
+    text: &[u8],
+    start: usize,
+    end: usize,
+) -> Result<(usize, usize, usize, usize), Error> {
+    if let Some(index) = find_angle_addr(text, start, end) {
+        let (display_start, display_end) = trim(text, start, index);
+        if display_start < display_end {
+            let (right_start, right_end) = trim(text, index + 1, end);
+            return if right_start < right_end && text[right_end - 1] == b'>' {
+                Ok((display_start, display_end, right_start, right_end - 1))
+            } else {
+                Err(Error::MissingEndBracket)
+            };
+        }
+    }
+    Ok((start, start, start, end))
+}
+
+///
+/// Find the `@` separating the `local-part` from the `domain`. This is the last `@` that is not
+/// within a quoted string or comment; if quotes or comments are unbalanced the last `@` in the
+/// text is used and any error is left to the component parsers.
+///
+pub(crate) const fn find_separator(text: &[u8], start: usize, end: usize) -> Option<usize> {
+    let mut separator = None;
+    let mut depth = 0_usize;
+    let mut in_quotes = false;
+    let mut index = start;
+    while index < end {
+        match text[index] {
+            b'\\' if in_quotes || depth > 0 => index += 1,
+            b'"' if depth == 0 => in_quotes = !in_quotes,
+            b'(' if !in_quotes => depth += 1,
+            b')' if !in_quotes && depth > 0 => depth -= 1,
+            b'@' if !in_quotes && depth == 0 => separator = Some(index),
+            _ => (),
+        }
+        index += 1;
+    }
+    if in_quotes || depth > 0 {
+        rfind(text, start, end, b'@')
+    } else {
+        separator
+    }
+}
+
+///
+/// Check the content of a `local-part` without any CFWS, which is either a quoted string or a
+/// `dot-atom-text`; its length is checked by the caller.
+///
+pub(crate) const fn local_part(text: &[u8], start: usize, end: usize) -> Result<(), Error> {
+    if start < end && text[start] == b'"' && text[end - 1] == b'"' {
+        // <= to handle a local part of a single `"`.
+        if end - start <= 2 {
+            Err(Error::LocalPartEmpty)
+        } else if is_qcontent(text, start + 1, end - 1) {
+            Ok(())
+        } else {
+            Err(Error::InvalidCharacter)
+        }
+    } else if is_dot_atom_text(text, start, end) {
+        Ok(())
+    } else {
+        Err(Error::InvalidCharacter)
+    }
+}
+
+///
+/// Check a single label of a `domain`. The first and last characters must be letters or digits,
+/// which is only checked here for ASCII characters; IDNA A-labels are also left to the caller.
+///
+pub(crate) const fn sub_domain(text: &[u8], start: usize, end: usize) -> Result<(), Error> {
+    // As per https://www.rfc-editor.org/rfc/rfc1034#section-3.5
+    // and https://html.spec.whatwg.org/multipage/input.html#valid-e-mail-address,
+    // at least one character must exist in a `subdomain`/`label` part of the domain
+    if start == end {
+        return Err(Error::SubDomainEmpty);
+    }
+
+    // As per https://www.rfc-editor.org/rfc/rfc1034#section-3.5,
+    // the domain label needs to start with a `letter`;
+    // however, https://html.spec.whatwg.org/multipage/input.html#valid-e-mail-address
+    // specifies a label can start
+    // with a `let-dig` (letter or digit), so we allow the wider range
+    if text[start].is_ascii() && !text[start].is_ascii_alphanumeric() {
+        return Err(Error::InvalidCharacter);
+    }
+    // Both specifications mentioned above require the last character to be a
+    // `let-dig` (letter or digit)
+    if text[end - 1].is_ascii() && !text[end - 1].is_ascii_alphanumeric() {
+        return Err(Error::InvalidCharacter);
+    }
+
+    if end - start > SUB_DOMAIN_MAX_LENGTH {
+        return Err(Error::SubDomainTooLong);
+    }
+
+    if !is_atom(text, start, end) {
+        return Err(Error::InvalidCharacter);
+    }
+
+    Ok(())
+}
+
+///
+/// Returns `true` if the text starts with the `IPv6:` tag, ignoring case.
+///
+pub(crate) const fn has_ipv6_tag(text: &[u8], start: usize, end: usize) -> bool {
+    const TAG: &[u8] = b"ipv6:";
+    if end - start < TAG.len() {
+        return false;
+    }
+    let mut index = 0;
+    while index < TAG.len() {
+        if !text[start + index].eq_ignore_ascii_case(&TAG[index]) {
+            return false;
+        }
+        index += 1;
+    }
+    true
+}
+
+///
+/// Parse the RFC 5321 `IPv4-address-literal` production, four decimal values in the range 0
+/// to 255 of no more than three digits each.
+///
+pub(crate) const fn ipv4_address(text: &[u8], start: usize, end: usize) -> Option<[u8; 4]> {
+    let mut octets = [0_u8; 4];
+    let mut count = 0;
+    let mut value = 0_u32;
+    let mut digits = 0;
+    let mut index = start;
+    while index <= end {
+        if index == end || text[index] == b'.' {
+            if digits == 0 || value > 255 || count == octets.len() {
+                return None;
+            }
+            octets[count] = value as u8;
+            count += 1;
+            value = 0;
+            digits = 0;
+        } else if text[index].is_ascii_digit() && digits < 3 {
+            value = value * 10 + (text[index] - b'0') as u32;
+            digits += 1;
+        } else {
+            return None;
+        }
+        index += 1;
+    }
+    if count == octets.len() {
+        Some(octets)
+    } else {
+        None
+    }
+}
+
+///
+/// Parse the RFC 5321 `IPv6-addr` production, following the tag, which covers the full,
+/// compressed, and IPv4-embedded forms from RFC 4291.
+///
+pub(crate) const fn ipv6_address(text: &[u8], start: usize, end: usize) -> Option<[u16; 8]> {
+    let mut index = start;
+    while index + 1 < end {
+        if text[index] == b':' && text[index + 1] == b':' {
+            let (mut segments, head_length) = match ipv6_groups(text, start, index, false) {
+                Some(head) => head,
+                None => return None,
+            };
+            let (tail, tail_length) = match ipv6_groups(text, index + 2, end, true) {
+                Some(tail) => tail,
+                None => return None,
+            };
+            // The "::" represents at least two groups of zeros.
+            if head_length + tail_length > segments.len() - 2 {
+                return None;
+            }
+            let mut group = 0;
+            while group < tail_length {
+                segments[segments.len() - tail_length + group] = tail[group];
+                group += 1;
+            }
+            return Some(segments);
+        }
+        index += 1;
+    }
+    match ipv6_groups(text, start, end, true) {
+        Some((segments, 8)) => Some(segments),
+        _ => None,
+    }
+}
+
+pub(crate) const fn is_atom(text: &[u8], start: usize, end: usize) -> bool {
+    let mut index = start;
+    while index < end {
+        if !is_atext(text[index] as char) {
+            return false;
+        }
+        index += 1;
+    }
+    start < end
+}
+
+pub(crate) const fn is_dot_atom_text(text: &[u8], start: usize, end: usize) -> bool {
+    let mut atom_start = start;
+    let mut index = start;
+    while index <= end {
+        if index == end || text[index] == b'.' {
+            if !is_atom(text, atom_start, index) {
+                return false;
+            }
+            atom_start = index + 1;
+        }
+        index += 1;
+    }
+    true
+}
+
+pub(crate) const fn is_qcontent(text: &[u8], start: usize, end: usize) -> bool {
+    let mut index = start;
+    while index < end {
+        let c = text[index] as char;
+        if c == '\\' {
+            // quoted-pair
+            index += 1;
+            if index == end || !is_vchar(text[index] as char) {
+                return false;
+            }
+        } else if !(is_wsp(c) || is_qtext_char(c)) {
+            // qtext
+            return false;
+        }
+        index += 1;
+    }
+    true
+}
+
+pub(crate) const fn is_atext(c: char) -> bool {
+    c.is_ascii_alphanumeric()
+        || matches!(
+            c,
+            '!' | '#'
+                | '$'
+                | '%'
+                | '&'
+                | '\''
+                | '*'
+                | '+'
+                | '-'
+                | '/'
+                | '='
+                | '?'
+                | '^'
+                | '_'
+                | '`'
+                | '{'
+                | '|'
+                | '}'
+                | '~'
+        )
+        || !c.is_ascii()
+}
+
+pub(crate) const fn is_vchar(c: char) -> bool {
+    matches!(c, '\x21'..='\x7E')
+}
+
+pub(crate) const fn is_wsp(c: char) -> bool {
+    c == ' ' || c == '\t'
+}
+
+pub(crate) const fn is_qtext_char(c: char) -> bool {
+    matches!(c, '\x21' | '\x23'..='\x5B' | '\x5D'..='\x7E') || !c.is_ascii()
+}
+
+pub(crate) const fn is_dtext_char(c: char) -> bool {
+    matches!(c, '\x21'..='\x5A' | '\x5E'..='\x7E') || !c.is_ascii()
+}
+
+pub(crate) const fn is_ctext_char(c: char) -> bool {
+    matches!(c, '\x21'..='\x27' | '\x2A'..='\x5B' | '\x5D'..='\x7E') || !c.is_ascii()
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Functions
+// ------------------------------------------------------------------------------------------------
+
+///
+/// Find the `<` that starts the `angle-addr` following a display name, ignoring any that are
+/// within a quoted string or comment in the display name.
+///
+const fn find_angle_addr(text: &[u8], start: usize, end: usize) -> Option<usize> {
+    let mut depth = 0_usize;
+    let mut in_quotes = false;
+    let mut index = start;
+    while index < end {
+        match text[index] {
+            b'\\' if in_quotes || depth > 0 => index += 1,
+            b'"' if depth == 0 => in_quotes = !in_quotes,
+            b'(' if !in_quotes => depth += 1,
+            b')' if !in_quotes && depth > 0 => depth -= 1,
+            b'<' if !in_quotes && depth == 0 => return Some(index),
+            _ => (),
+        }
+        index += 1;
+    }
+    None
+}
+
+///
+/// Parse a sequence of `IPv6-hex` groups separated by `:`, returning the groups and their
+/// number. If `allow_ipv4` is set the last group may instead be an `IPv4-address-literal` which
+/// is returned as two groups.
+///
+const fn ipv6_groups(
+    text: &[u8],
+    start: usize,
+    end: usize,
+    allow_ipv4: bool,
+) -> Option<([u16; 8], usize)> {
+    let mut groups = [0_u16; 8];
+    let mut length = 0;
+    if start == end {
+        return Some((groups, length));
+    }
+    let mut group_start = start;
+    let mut index = start;
+    while index <= end {
+        if index == end || text[index] == b':' {
+            if allow_ipv4 && index == end && contains(text, group_start, index, b'.') {
+                let octets = match ipv4_address(text, group_start, index) {
+                    Some(octets) => octets,
+                    None => return None,
+                };
+                if length + 2 > groups.len() {
+                    return None;
+                }
+                groups[length] = u16::from_be_bytes([octets[0], octets[1]]);
+                groups[length + 1] = u16::from_be_bytes([octets[2], octets[3]]);
+                length += 2;
+            } else if group_start < index && index - group_start <= 4 && length < groups.len() {
+                groups[length] = match hex_value(text, group_start, index) {
+                    Some(value) => value,
+                    None => return None,
+                };
+                length += 1;
+            } else {
+                return None;
+            }
+            group_start = index + 1;
+        }
+        index += 1;
+    }
+    Some((groups, length))
+}
+
+///
+/// Parse the hexadecimal digits between `start` and `end`, of which there are no more than four.
+///
+const fn hex_value(text: &[u8], start: usize, end: usize) -> Option<u16> {
+    let mut value = 0_u16;
+    let mut index = start;
+    while index < end {
+        let digit = match text[index] {
+            b @ b'0'..=b'9' => b - b'0',
+            b @ b'a'..=b'f' => b - b'a' + 10,
+            b @ b'A'..=b'F' => b - b'A' + 10,
+            _ => return None,
+        };
+        value = (value << 4) | digit as u16;
+        index += 1;
+    }
+    Some(value)
+}
+
+///
+/// Returns the bounds of the text between `start` and `end` without any leading or trailing
+/// white space, using the same definition of white space as `str::trim`.
+///
+const fn trim(text: &[u8], start: usize, end: usize) -> (usize, usize) {
+    let mut start = start;
+    while start < end {
+        let (c, len) = decode(text, start);
+        if !is_whitespace(c) {
+            break;
+        }
+        start += len;
+    }
+    let mut end = end;
+    while start < end {
+        let mut char_start = end - 1;
+        while text[char_start] & 0xC0 == 0x80 {
+            char_start -= 1;
+        }
+        if !is_whitespace(decode(text, char_start).0) {
+            break;
+        }
+        end = char_start;
+    }
+    (start, end)
+}
+
+///
+/// Decode the UTF-8 encoded character starting at `index`, returning the code point and the
+/// length of its encoding.
+///
+const fn decode(text: &[u8], index: usize) -> (u32, usize) {
+    let b = text[index] as u32;
+    if b < 0x80 {
+        (b, 1)
+    } else if b < 0xE0 {
+        (((b & 0x1F) << 6) | (text[index + 1] as u32 & 0x3F), 2)
+    } else if b < 0xF0 {
+        (
+            ((b & 0x0F) << 12)
+                | ((text[index + 1] as u32 & 0x3F) << 6)
+                | (text[index + 2] as u32 & 0x3F),
+            3,
+        )
+    } else {
+        (
+            ((b & 0x07) << 18)
+                | ((text[index + 1] as u32 & 0x3F) << 12)
+                | ((text[index + 2] as u32 & 0x3F) << 6)
+                | (text[index + 3] as u32 & 0x3F),
+            4,
+        )
+    }
+}
+
+///
+/// The Unicode `White_Space` property, as used by `char::is_whitespace`.
+///
+const fn is_whitespace(c: u32) -> bool {
+    matches!(
+        c,
+        0x09..=0x0D
+            | 0x20
+            | 0x85
+            | 0xA0
+            | 0x1680
+            | 0x2000..=0x200A
+            | 0x2028
+            | 0x2029
+            | 0x202F
+            | 0x205F
+            | 0x3000
+    )
+}
+
+const fn contains(text: &[u8], start: usize, end: usize, b: u8) -> bool {
+    rfind(text, start, end, b).is_some()
+}
+
+const fn rfind(text: &[u8], start: usize, end: usize, b: u8) -> Option<usize> {
+    let mut index = end;
+    while index > start {
+        index -= 1;
+        if text[index] == b {
+            return Some(index);
+        }
+    }
+    None
+}
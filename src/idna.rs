@@ -0,0 +1,550 @@
+/*!
+Support for converting domain names between their Unicode (U-label) and ASCII (A-label) forms.
+Labels are converted with the Punycode algorithm described in RFC 3492, [_Punycode: A Bootstring
+encoding of Unicode for Internationalized Domain Names in Applications
+(IDNA)_](https://tools.ietf.org/html/rfc3492).
+
+With the `idna_support` feature the domain is processed following
+[UTS #46](https://www.unicode.org/reports/tr46/) in its non-transitional mode, using the
+mapping and validity tables, and the *CheckJoiners* and *CheckBidi* rules, of the `idna` crate.
+U-labels must also meet the IDNA2008 rules below, so that, for example, a label may not begin
+with a symbol.
+
+Without the feature the conversion only approximates UTS #46 and IDNA2008 and is **not**
+conformant. The mapping step is limited to case folding, and the mapping of the full-width and
+ideographic full stops to `.`; any other non-ASCII character is accepted in a U-label, and the
+contextual rules for joiners and the Bidi rule are not applied. The normalization step requires
+the `normalization_support` feature, without it labels are assumed to already be in
+Normalization Form C.
+*/
+
+use crate::{Error, DOMAIN_MAX_LENGTH, DOT, SUB_DOMAIN_MAX_LENGTH};
+#[cfg(feature = "idna_support")]
+use ::idna::uts46::{AsciiDenyList, Hyphens, Uts46};
+
+// ------------------------------------------------------------------------------------------------
+// Implementations
+// ------------------------------------------------------------------------------------------------
+
+const ACE_PREFIX: &str = "xn--";
+const HYPHEN: char = '-';
+
+// Full stops that UTS #46 maps to U+002E.
+const IDEOGRAPHIC_FULL_STOP: char = '\u{3002}';
+const FULLWIDTH_FULL_STOP: char = '\u{FF0E}';
+const HALFWIDTH_IDEOGRAPHIC_FULL_STOP: char = '\u{FF61}';
+
+// Bootstring parameters for Punycode, RFC 3492 §5.
+const BASE: u32 = 36;
+const T_MIN: u32 = 1;
+const T_MAX: u32 = 26;
+const SKEW: u32 = 38;
+const DAMP: u32 = 700;
+const INITIAL_BIAS: u32 = 72;
+const INITIAL_N: u32 = 0x80;
+const DELIMITER: char = '-';
+
+// ------------------------------------------------------------------------------------------------
+// Crate Functions
+// ------------------------------------------------------------------------------------------------
+
+///
+/// Convert `domain` to its ASCII form, all U-labels are converted into A-labels and all
+/// existing A-labels are validated.
+///
+pub(crate) fn domain_to_ascii(domain: &str) -> Result<String, Error> {
+    let labels = map_domain(domain)?
+        .split(DOT)
+        .map(|label| {
+            if is_ace_label(label) {
+                let _ = decode_a_label(label)?;
+                Ok(label.to_string())
+            } else if label.is_ascii() {
+                Ok(label.to_string())
+            } else {
+                validate_u_label(label)?;
+                let a_label = format!("{}{}", ACE_PREFIX, punycode_encode(label)?);
+                if a_label.len() > SUB_DOMAIN_MAX_LENGTH {
+                    Error::SubDomainTooLong.into()
+                } else {
+                    Ok(a_label)
+                }
+            }
+        })
+        .collect::<Result<Vec<String>, Error>>()?;
+    let domain = labels.join(&DOT.to_string());
+    if domain.len() > DOMAIN_MAX_LENGTH {
+        Error::DomainTooLong.into()
+    } else {
+        Ok(domain)
+    }
+}
+
+///
+/// Convert `domain` to its Unicode form, all A-labels are validated and converted into
+/// U-labels.
+///
+pub(crate) fn domain_to_unicode(domain: &str) -> Result<String, Error> {
+    let labels = map_domain(domain)?
+        .split(DOT)
+        .map(|label| {
+            if is_ace_label(label) {
+                decode_a_label(label)
+            } else {
+                if !label.is_ascii() {
+                    validate_u_label(label)?;
+                }
+                Ok(label.to_string())
+            }
+        })
+        .collect::<Result<Vec<String>, Error>>()?;
+    Ok(labels.join(&DOT.to_string()))
+}
+
+///
+/// Returns `true` if `label` begins with the ACE prefix `xn--`, in any case.
+///
+pub(crate) fn is_ace_label(label: &str) -> bool {
+    label
+        .get(..ACE_PREFIX.len())
+        .map(|prefix| prefix.eq_ignore_ascii_case(ACE_PREFIX))
+        .unwrap_or_default()
+}
+
+///
+/// Decode an A-label, which must include the ACE prefix, into the U-label it represents.
+/// The label is only valid if it decodes to a valid U-label containing at least one non-ASCII
+/// character, and that U-label encodes back to the same A-label.
+///
+pub(crate) fn decode_a_label(label: &str) -> Result<String, Error> {
+    if !label.is_ascii() {
+        return Error::InvalidALabel.into();
+    }
+    let encoded = label[ACE_PREFIX.len()..].to_ascii_lowercase();
+    let u_label = punycode_decode(&encoded).ok_or(Error::InvalidALabel)?;
+    if u_label.is_ascii()
+        || validate_u_label(&u_label).is_err()
+        || punycode_encode(&u_label).ok() != Some(encoded)
+    {
+        Error::InvalidALabel.into()
+    } else {
+        Ok(u_label)
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Functions
+// ------------------------------------------------------------------------------------------------
+
+///
+/// The UTS #46 mapping, normalization, and domain-wide validation steps. Existing A-labels are
+/// validated first so that they report `Error::InvalidALabel`, and are then returned in their
+/// ASCII form.
+///
+#[cfg(feature = "idna_support")]
+fn map_domain(domain: &str) -> Result<String, Error> {
+    let labels: Vec<&str> = domain.split(is_full_stop).collect();
+    for label in labels.iter().filter(|label| is_ace_label(label)) {
+        let _ = decode_a_label(label)?;
+    }
+    let (mapped, result) =
+        Uts46::new().to_unicode(domain.as_bytes(), AsciiDenyList::EMPTY, Hyphens::Allow);
+    if result.is_err() {
+        return Error::InvalidCharacter.into();
+    }
+    let mapped: Vec<&str> = mapped.split(DOT).collect();
+    if mapped.len() != labels.len() {
+        return Ok(mapped.join(&DOT.to_string()));
+    }
+    Ok(labels
+        .iter()
+        .zip(mapped)
+        .map(|(label, mapped)| {
+            if is_ace_label(label) {
+                label.to_ascii_lowercase()
+            } else {
+                mapped.to_string()
+            }
+        })
+        .collect::<Vec<String>>()
+        .join(&DOT.to_string()))
+}
+
+///
+/// The UTS #46 mapping and normalization steps, limited to case folding and the full stops
+/// without the `idna_support` feature.
+///
+#[cfg(not(feature = "idna_support"))]
+fn map_domain(domain: &str) -> Result<String, Error> {
+    let mapped: String = domain
+        .chars()
+        .map(|c| if is_full_stop(c) { DOT } else { c })
+        .flat_map(char::to_lowercase)
+        .collect();
+    Ok(normalize(mapped))
+}
+
+fn is_full_stop(c: char) -> bool {
+    matches!(
+        c,
+        DOT | IDEOGRAPHIC_FULL_STOP | FULLWIDTH_FULL_STOP | HALFWIDTH_IDEOGRAPHIC_FULL_STOP
+    )
+}
+
+#[cfg(feature = "normalization_support")]
+pub(crate) fn normalize(text: String) -> String {
+    use unicode_normalization::UnicodeNormalization;
+    text.nfc().collect()
+}
+
+#[cfg(not(feature = "normalization_support"))]
+pub(crate) fn normalize(text: String) -> String {
+    text
+}
+
+#[cfg(feature = "normalization_support")]
+fn is_normalized(text: &str) -> bool {
+    unicode_normalization::is_nfc(text)
+}
+
+#[cfg(not(feature = "normalization_support"))]
+fn is_normalized(_: &str) -> bool {
+    true
+}
+
+///
+/// A label is valid if UTS #46 processing succeeds and does not change it, so that it contains
+/// only valid code points and meets the *CheckJoiners* and *CheckBidi* rules.
+///
+#[cfg(feature = "idna_support")]
+fn is_valid_label(label: &str) -> bool {
+    match Uts46::new().to_unicode(label.as_bytes(), AsciiDenyList::EMPTY, Hyphens::Allow) {
+        (mapped, Ok(())) => mapped == label,
+        _ => false,
+    }
+}
+
+#[cfg(not(feature = "idna_support"))]
+fn is_valid_label(_: &str) -> bool {
+    true
+}
+
+///
+/// The label validity criteria from UTS #46 §4.1 that do not require the IDNA tables, and
+/// with the `idna_support` feature those that do.
+///
+fn validate_u_label(label: &str) -> Result<(), Error> {
+    if label.is_empty() {
+        Error::SubDomainEmpty.into()
+    } else if label.starts_with(HYPHEN)
+        || label.ends_with(HYPHEN)
+        || label.chars().skip(2).take(2).eq("--".chars())
+        || label.contains(DOT)
+        || !label.starts_with(char::is_alphanumeric)
+        || !label
+            .chars()
+            .all(|c| c == HYPHEN || c.is_alphanumeric() || !c.is_ascii())
+        || label.chars().any(|c| c.is_uppercase() || c.is_control())
+        || !is_normalized(label)
+        || !is_valid_label(label)
+    {
+        Error::InvalidCharacter.into()
+    } else {
+        Ok(())
+    }
+}
+
+fn adapt(delta: u32, num_points: u32, first_time: bool) -> u32 {
+    let mut delta = if first_time { delta / DAMP } else { delta / 2 };
+    delta += delta / num_points;
+    let mut k = 0;
+    while delta > ((BASE - T_MIN) * T_MAX) / 2 {
+        delta /= BASE - T_MIN;
+        k += BASE;
+    }
+    k + (((BASE - T_MIN + 1) * delta) / (delta + SKEW))
+}
+
+fn threshold(k: u32, bias: u32) -> u32 {
+    if k <= bias {
+        T_MIN
+    } else if k >= bias + T_MAX {
+        T_MAX
+    } else {
+        k - bias
+    }
+}
+
+fn encode_digit(d: u32) -> char {
+    if d < 26 {
+        char::from(b'a' + d as u8)
+    } else {
+        char::from(b'0' + (d - 26) as u8)
+    }
+}
+
+fn decode_digit(c: char) -> Option<u32> {
+    match c {
+        'a'..='z' => Some(c as u32 - 'a' as u32),
+        'A'..='Z' => Some(c as u32 - 'A' as u32),
+        '0'..='9' => Some(c as u32 - '0' as u32 + 26),
+        _ => None,
+    }
+}
+
+///
+/// Punycode encoding, RFC 3492 §6.3.
+///
+fn punycode_encode(input: &str) -> Result<String, Error> {
+    let input: Vec<u32> = input.chars().map(u32::from).collect();
+    let mut output: String = input
+        .iter()
+        .filter(|c| **c < INITIAL_N)
+        .map(|c| char::from(*c as u8))
+        .collect();
+    let basic_length = output.len() as u32;
+    if basic_length > 0 {
+        output.push(DELIMITER);
+    }
+
+    let mut n = INITIAL_N;
+    let mut delta: u32 = 0;
+    let mut bias = INITIAL_BIAS;
+    let mut handled = basic_length;
+    while (handled as usize) < input.len() {
+        let m = input
+            .iter()
+            .copied()
+            .filter(|c| *c >= n)
+            .min()
+            .ok_or(Error::InvalidALabel)?;
+        delta = (m - n)
+            .checked_mul(handled + 1)
+            .and_then(|d| delta.checked_add(d))
+            .ok_or(Error::InvalidALabel)?;
+        n = m;
+        for c in &input {
+            if *c < n {
+                delta = delta.checked_add(1).ok_or(Error::InvalidALabel)?;
+            } else if *c == n {
+                let mut q = delta;
+                let mut k = BASE;
+                loop {
+                    let t = threshold(k, bias);
+                    if q < t {
+                        break;
+                    }
+                    output.push(encode_digit(t + (q - t) % (BASE - t)));
+                    q = (q - t) / (BASE - t);
+                    k += BASE;
+                }
+                output.push(encode_digit(q));
+                bias = adapt(delta, handled + 1, handled == basic_length);
+                delta = 0;
+                handled += 1;
+            }
+        }
+        delta += 1;
+        n += 1;
+    }
+    Ok(output)
+}
+
+///
+/// Punycode decoding, RFC 3492 §6.2.
+///
+fn punycode_decode(input: &str) -> Option<String> {
+    let (basic, extended) = match input.rfind(DELIMITER) {
+        Some(index) => (&input[..index], &input[index + 1..]),
+        None => ("", input),
+    };
+    if !basic.is_ascii() {
+        return None;
+    }
+    let mut output: Vec<char> = basic.chars().collect();
+
+    let mut n = INITIAL_N;
+    let mut i: u32 = 0;
+    let mut bias = INITIAL_BIAS;
+    let mut char_iter = extended.chars();
+    while !char_iter.as_str().is_empty() {
+        let old_i = i;
+        let mut w: u32 = 1;
+        let mut k = BASE;
+        loop {
+            let digit = decode_digit(char_iter.next()?)?;
+            i = digit.checked_mul(w).and_then(|d| i.checked_add(d))?;
+            let t = threshold(k, bias);
+            if digit < t {
+                break;
+            }
+            w = w.checked_mul(BASE - t)?;
+            k += BASE;
+        }
+        let length = output.len() as u32 + 1;
+        bias = adapt(i - old_i, length, old_i == 0);
+        n = n.checked_add(i / length)?;
+        i %= length;
+        if n < INITIAL_N {
+            return None;
+        }
+        output.insert(i as usize, char::from_u32(n)?);
+        i += 1;
+    }
+    Some(output.into_iter().collect())
+}
+
+// ------------------------------------------------------------------------------------------------
+// Unit Tests
+// ------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Sample strings from RFC 3492 §7.1.
+    const SAMPLES: &[(&str, &str)] = &[
+        (
+            "\u{0644}\u{064A}\u{0647}\u{0645}\u{0627}\u{0628}\u{062A}\u{0643}\u{0644}\u{0645}\u{0648}\u{0634}\u{0639}\u{0631}\u{0628}\u{064A}\u{061F}",
+            "egbpdaj6bu4bxfgehfvwxn",
+        ),
+        (
+            "\u{4ED6}\u{4EEC}\u{4E3A}\u{4EC0}\u{4E48}\u{4E0D}\u{8BF4}\u{4E2D}\u{6587}",
+            "ihqwcrb4cv8a8dqg056pqjye",
+        ),
+        ("Pročprostěnemluvíčesky", "Proprostnemluvesky-uyb24dma41a"),
+        ("3年B組金八先生", "3B-ww4c5e180e575a65lsy2b"),
+        (
+            "安室奈美恵-with-SUPER-MONKEYS",
+            "-with-SUPER-MONKEYS-pc58ag80a8qai00g7n9n",
+        ),
+        ("ひとつ屋根の下2", "2-u9tlzr9756bt3uc0v"),
+        ("bücher", "bcher-kva"),
+    ];
+
+    #[test]
+    fn test_punycode_samples() {
+        for (unicode, ascii) in SAMPLES {
+            println!(">> {} <=> {}", unicode, ascii);
+            assert_eq!(punycode_encode(unicode).unwrap(), *ascii);
+            assert_eq!(punycode_decode(ascii).unwrap(), *unicode);
+        }
+    }
+
+    #[test]
+    fn test_punycode_bad_input() {
+        assert_eq!(punycode_decode("a!"), None);
+        assert_eq!(punycode_decode("99999999999"), None);
+        assert_eq!(punycode_decode("ü-abc"), None);
+    }
+
+    #[test]
+    fn test_domain_to_ascii() {
+        assert_eq!(domain_to_ascii("example.com").unwrap(), "example.com");
+        assert_eq!(
+            domain_to_ascii("Bücher.Example").unwrap(),
+            "xn--bcher-kva.example"
+        );
+        assert_eq!(
+            domain_to_ascii("例子。广告").unwrap(),
+            "xn--fsqu00a.xn--4rr70v"
+        );
+        assert_eq!(
+            domain_to_ascii("xn--bcher-kva.com").unwrap(),
+            "xn--bcher-kva.com"
+        );
+        assert_eq!(domain_to_ascii("faß.de").unwrap(), "xn--fa-hia.de");
+    }
+
+    #[test]
+    fn test_domain_to_unicode() {
+        assert_eq!(
+            domain_to_unicode("xn--bcher-kva.example").unwrap(),
+            "bücher.example"
+        );
+        assert_eq!(
+            domain_to_unicode("XN--BCHER-KVA.Example").unwrap(),
+            "bücher.example"
+        );
+        assert_eq!(
+            domain_to_unicode("xn--fsqu00a.xn--4rr70v").unwrap(),
+            "例子.广告"
+        );
+        assert_eq!(domain_to_unicode("пример.рф").unwrap(), "пример.рф");
+    }
+
+    #[test]
+    fn test_invalid_a_labels() {
+        assert_eq!(decode_a_label("xn--"), Err(Error::InvalidALabel));
+        assert_eq!(decode_a_label("xn--abc-"), Err(Error::InvalidALabel));
+        assert_eq!(decode_a_label("xn--ls8h"), Err(Error::InvalidALabel));
+        assert_eq!(decode_a_label("xn--ab!cd"), Err(Error::InvalidALabel));
+        assert_eq!(decode_a_label("xn--Bcher-kva"), Ok("bücher".to_string()));
+        assert_eq!(
+            domain_to_unicode("xn--zz.example"),
+            Err(Error::InvalidALabel)
+        );
+    }
+
+    #[test]
+    fn test_invalid_u_labels() {
+        assert_eq!(domain_to_ascii("-bücher.com"), Err(Error::InvalidCharacter));
+        assert_eq!(
+            domain_to_ascii("bü--cher.com"),
+            Err(Error::InvalidCharacter)
+        );
+        assert_eq!(
+            domain_to_ascii(&format!("{}.com", "ü".repeat(60))),
+            Err(Error::SubDomainTooLong)
+        );
+    }
+
+    #[cfg(feature = "idna_support")]
+    #[test]
+    fn test_uts46_mapping() {
+        assert_eq!(domain_to_ascii("ｅｘａmple.com").unwrap(), "example.com");
+        assert_eq!(domain_to_unicode("ｅｘａmple.com").unwrap(), "example.com");
+        assert_eq!(domain_to_ascii("ＢＵＣＨ.de").unwrap(), "buch.de");
+    }
+
+    #[cfg(feature = "idna_support")]
+    #[test]
+    fn test_uts46_validity() {
+        // CheckJoiners: a ZERO WIDTH JOINER is only allowed after a virama.
+        assert_eq!(
+            domain_to_ascii("a\u{200D}b.com"),
+            Err(Error::InvalidCharacter)
+        );
+        assert!(domain_to_ascii("\u{0915}\u{094D}\u{200D}.com").is_ok());
+        // CheckBidi: an RTL label must not begin with a digit, and an LTR label in a domain
+        // containing RTL labels must not contain RTL characters.
+        assert_eq!(
+            domain_to_ascii("1\u{05D0}.com"),
+            Err(Error::InvalidCharacter)
+        );
+        assert_eq!(
+            domain_to_ascii("a\u{05D0}.com"),
+            Err(Error::InvalidCharacter)
+        );
+        assert_eq!(
+            domain_to_ascii("\u{05D0}.0a.com"),
+            Err(Error::InvalidCharacter)
+        );
+        assert!(domain_to_ascii("\u{05D0}\u{05D1}.com").is_ok());
+        // Disallowed code points.
+        assert_eq!(
+            domain_to_ascii("a\u{FFFD}b.com"),
+            Err(Error::InvalidCharacter)
+        );
+        assert_eq!(decode_a_label("xn--ab-m1t"), Err(Error::InvalidALabel));
+    }
+
+    #[cfg(feature = "normalization_support")]
+    #[test]
+    fn test_normalization() {
+        assert_eq!(
+            domain_to_ascii("bu\u{0308}cher.com").unwrap(),
+            "xn--bcher-kva.com"
+        );
+    }
+}
@@ -4,8 +4,11 @@ A Rust crate providing an implementation of an RFC-compliant `EmailAddress` newt
 Primarily for validation, the `EmailAddress` type is constructed with `FromStr::from_str` which will raise any
 parsing errors. Prior to constructions the functions `is_valid`, `is_valid_local_part`, and `is_valid_domain` may
 also be used to test for validity without constructing an instance. This supports all of the RFC ASCII and UTF-8
-character set rules, quoted and unquoted local parts but does not yet support all of the productions required for SMTP
-headers; folding whitespace, comments, etc.
+character set rules, quoted and unquoted local parts, and optionally comments and folding white space (`CFWS`)
+around the atoms of the local part and labels of the domain.
+
+The `AddressList` type parses the comma-separated list of mailboxes and groups found in address header fields such
+as `To:` and `Cc:`, each mailbox being parsed as an `EmailAddress`.
 
 ```text
 "Simon Johnston <johnstonsk@gmail.com>"
@@ -174,6 +177,48 @@ dtext           =   %d33-90 /          ; Printable US-ASCII
                     obs-dtext          ;  "[", "]", or "\"
 ```
 
+From RFC 5321, §4.1.3. [Address Literals](https://tools.ietf.org/html/rfc5321#section-4.1.3):
+
+```ebnf
+IPv4-address-literal    = Snum 3("."  Snum)
+
+IPv6-address-literal    = "IPv6:" IPv6-addr
+
+General-address-literal = Standardized-tag ":" 1*dcontent
+
+Standardized-tag        = Ldh-str
+                        ; Standardized-tag MUST be specified in a
+                        ; Standards-Track RFC and registered with IANA
+
+dcontent                = %d33-90 / ; Printable US-ASCII
+                          %d94-126  ; excl. "[", "\", "]"
+
+Snum                    = 1*3DIGIT
+                        ; representing a decimal integer
+                        ; value in the range 0 through 255
+
+IPv6-addr               = IPv6-full / IPv6-comp / IPv6v4-full / IPv6v4-comp
+
+IPv6-hex                = 1*4HEXDIG
+
+IPv6-full               = IPv6-hex 7(":" IPv6-hex)
+
+IPv6-comp               = [IPv6-hex *5(":" IPv6-hex)] "::"
+                          [IPv6-hex *5(":" IPv6-hex)]
+                        ; The "::" represents at least 2 16-bit groups of
+                        ; zeros.  No more than 6 groups in addition to the
+                        ; "::" may be present.
+
+IPv6v4-full             = IPv6-hex 5(":" IPv6-hex) ":" IPv4-address-literal
+
+IPv6v4-comp             = [IPv6-hex *3(":" IPv6-hex)] "::"
+                          [IPv6-hex *3(":" IPv6-hex) ":"]
+                          IPv4-address-literal
+                        ; The "::" represents at least 2 16-bit groups of
+                        ; zeros.  No more than 4 groups in addition to the
+                        ; "::" and IPv4-address-literal may be present.
+```
+
 RFC 3696, §3. [Restrictions on email addresses](https://tools.ietf.org/html/rfc3696#section-3)
 describes in detail the quoting of characters in an address.
 
@@ -297,10 +342,58 @@ An informal description can be found on [Wikipedia](https://en.wikipedia.org/wik
 
 #[cfg(feature = "serde_support")]
 use serde::{Deserialize, Serialize, Serializer};
+use std::borrow::Cow;
+use std::cmp::Ordering;
 use std::fmt::{Debug, Display, Formatter};
-use std::hash::Hash;
+use std::hash::{Hash, Hasher};
+use std::iter::Peekable;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
 use std::str::FromStr;
 
+mod display;
+use display::{decode_display_name, encode_display_name, is_phrase};
+
+mod idna;
+use idna::{decode_a_label, domain_to_ascii, domain_to_unicode, is_ace_label};
+
+mod builder;
+pub use builder::EmailAddressBuilder;
+
+mod mailbox;
+pub use mailbox::{AddrSpec, Domain, LocalPart, Mailbox};
+
+mod list;
+pub use list::{Address, AddressList, Group};
+
+mod mailto;
+pub use mailto::MailtoUri;
+
+mod parse_error;
+use parse_error::{locate, span_of};
+pub use parse_error::{Component, ParseError};
+
+mod borrowed;
+pub use borrowed::EmailAddressRef;
+
+mod grammar;
+use grammar::{is_atext, is_ctext_char, is_dtext_char, is_qtext_char, is_vchar, is_wsp};
+
+mod literal;
+
+mod canonical;
+use canonical::normalize_address;
+pub use canonical::{IdnaForm, NormalizeOptions};
+
+mod provider;
+pub use provider::{DomainRules, ProviderRegistry, ProviderRules};
+
+mod smtp;
+pub use smtp::{Path, ReversePath};
+
+mod diagnostic;
+use diagnostic::{check_address, diagnose_address};
+pub use diagnostic::{Diagnosis, Finding, Severity};
+
 // ------------------------------------------------------------------------------------------------
 // Public Types
 // ------------------------------------------------------------------------------------------------
@@ -344,6 +437,22 @@ pub enum Error {
     MissingDisplayName,
     /// An email enclosed within <...> is missing the final '>'.
     MissingEndBracket,
+    /// A group within an address list was malformed, unnamed, nested, or not terminated by ';'.
+    InvalidGroup,
+    /// An address list contained no addresses.
+    AddressListEmpty,
+    /// A `mailto` URI was malformed, either the scheme or the percent-encoding was invalid.
+    InvalidUri,
+    /// A `sub-domain` beginning with `xn--` is not a valid IDNA A-label.
+    InvalidALabel,
+    /// The address contains non-ASCII characters that require the SMTPUTF8 extension, but
+    /// this is unsupported by parser configuration.
+    RequiresSmtpUtf8,
+    /// An SMTP path was not enclosed within <...>, had a malformed source route, or was null
+    /// where a mailbox is required.
+    InvalidPath,
+    /// An SMTP path is too long.
+    PathTooLong,
 }
 
 ///
@@ -382,14 +491,14 @@ pub struct Options {
     ///
     /// assert!(
     ///     EmailAddress::parse_with_options(
-    ///         "email@[127.0.0.256]",
+    ///         "email@[127.0.0.1]",
     ///         Options::default().with_domain_literal()
     ///     ).is_ok()
     /// );
     ///
     /// assert_eq!(
     ///     EmailAddress::parse_with_options(
-    ///         "email@[127.0.0.256]",
+    ///         "email@[127.0.0.1]",
     ///         Options::default().without_domain_literal()
     ///     ),
     ///     Err(Error::UnsupportedDomainLiteral),
@@ -398,6 +507,84 @@ pub struct Options {
     ///
     pub allow_domain_literal: bool,
 
+    ///
+    /// Specifies if IPv4 address literals, `[192.168.2.1]`, are allowed when domain literals
+    /// are allowed. Defaults to `true`.
+    ///
+    /// ```rust
+    /// use email_address::*;
+    ///
+    /// assert_eq!(
+    ///     EmailAddress::parse_with_options(
+    ///         "email@[127.0.0.256]",
+    ///         Options::default().with_ipv4_literal()
+    ///     ),
+    ///     Err(Error::InvalidIPAddress),
+    /// );
+    ///
+    /// assert_eq!(
+    ///     EmailAddress::parse_with_options(
+    ///         "email@[127.0.0.1]",
+    ///         Options::default().without_ipv4_literal()
+    ///     ),
+    ///     Err(Error::UnsupportedDomainLiteral),
+    /// );
+    /// ```
+    ///
+    pub allow_ipv4_literal: bool,
+
+    ///
+    /// Specifies if IPv6 address literals, `[IPv6:2001:db8::1]`, are allowed when domain
+    /// literals are allowed. Defaults to `true`.
+    ///
+    /// ```rust
+    /// use email_address::*;
+    ///
+    /// assert_eq!(
+    ///     EmailAddress::parse_with_options(
+    ///         "email@[IPv6:2001:db8::12345]",
+    ///         Options::default().with_ipv6_literal()
+    ///     ),
+    ///     Err(Error::InvalidIPAddress),
+    /// );
+    ///
+    /// assert_eq!(
+    ///     EmailAddress::parse_with_options(
+    ///         "email@[IPv6:2001:db8::1]",
+    ///         Options::default().without_ipv6_literal()
+    ///     ),
+    ///     Err(Error::UnsupportedDomainLiteral),
+    /// );
+    /// ```
+    ///
+    pub allow_ipv6_literal: bool,
+
+    ///
+    /// Specifies if general address literals, `[tag:content]`, are allowed when domain
+    /// literals are allowed. Defaults to `false`, in which case a literal that is neither an
+    /// IPv4 nor IPv6 address is reported as an invalid IP address.
+    ///
+    /// ```rust
+    /// use email_address::*;
+    ///
+    /// assert!(
+    ///     EmailAddress::parse_with_options(
+    ///         "email@[x-tag:some-content]",
+    ///         Options::default().with_general_literal()
+    ///     ).is_ok()
+    /// );
+    ///
+    /// assert_eq!(
+    ///     EmailAddress::parse_with_options(
+    ///         "email@[x-tag:some-content]",
+    ///         Options::default().without_general_literal()
+    ///     ),
+    ///     Err(Error::InvalidIPAddress),
+    /// );
+    /// ```
+    ///
+    pub allow_general_literal: bool,
+
     ///
     /// Specified whether display text is allowed. Defaults to `true`. If you want strict
     /// email-only checking setting this to `false` will remove support for the prefix string
@@ -424,16 +611,122 @@ pub struct Options {
     /// ```
     ///
     pub allow_display_text: bool,
+
+    ///
+    /// Specifies whether comments and folding white space (`CFWS`) are allowed around the
+    /// atoms of the `local-part` and the labels of the `domain`. Defaults to `false`.
+    ///
+    /// ```rust
+    /// use email_address::*;
+    ///
+    /// assert!(
+    ///     EmailAddress::parse_with_options(
+    ///         "john(work)@example.com (John)",
+    ///         Options::default().with_comments()
+    ///     ).is_ok()
+    /// );
+    ///
+    /// assert_eq!(
+    ///     EmailAddress::parse_with_options(
+    ///         "john(work@example.com",
+    ///         Options::default().with_comments()
+    ///     ),
+    ///     Err(Error::InvalidComment),
+    /// );
+    ///
+    /// assert_eq!(
+    ///     EmailAddress::parse_with_options(
+    ///         "john(work)@example.com",
+    ///         Options::default().without_comments()
+    ///     ),
+    ///     Err(Error::InvalidCharacter),
+    /// );
+    /// ```
+    ///
+    pub allow_comments: bool,
+
+    ///
+    /// Specifies whether the address may require the SMTPUTF8 extension (RFC 6531), that is
+    /// whether non-ASCII characters are allowed in the `local-part`. When `false` the
+    /// `local-part` and any `domain-literal` are restricted to ASCII, while a Unicode `domain`
+    /// is allowed only if it can be converted to A-labels. Defaults to `true`.
+    ///
+    /// ```rust
+    /// use email_address::*;
+    ///
+    /// assert!(
+    ///     EmailAddress::parse_with_options(
+    ///         "simon@例子.广告",
+    ///         Options::default().without_smtputf8()
+    ///     ).is_ok()
+    /// );
+    ///
+    /// assert_eq!(
+    ///     EmailAddress::parse_with_options(
+    ///         "用户@例子.广告",
+    ///         Options::default().without_smtputf8()
+    ///     ),
+    ///     Err(Error::RequiresSmtpUtf8),
+    /// );
+    /// ```
+    ///
+    pub allow_smtputf8: bool,
+}
+
+///
+/// The host identified by the `domain` of an email address, either a domain name or one of the
+/// RFC 5321 address literal forms.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Host<'a> {
+    /// A domain name, for which an MX lookup is usually performed.
+    Domain(&'a str),
+    /// An IPv4 address literal, such as `[192.168.2.1]`.
+    Ipv4(Ipv4Addr),
+    /// An IPv6 address literal, such as `[IPv6:2001:db8::1]`.
+    Ipv6(Ipv6Addr),
+    /// A general address literal, such as `[x-tag:content]`.
+    General {
+        /// The standardized tag preceding the `:`.
+        tag: &'a str,
+        /// The content following the `:`.
+        content: &'a str,
+    },
 }
 
 ///
 /// Type representing a single email address. This is basically a wrapper around a String, the
 /// email address is parsed for correctness with `FromStr::from_str`, which is the only want to
-/// create an instance. The various components of the email _are not_ parsed out to be accessible
-/// independently.
+/// create an instance. The boundaries of the various components of the email are recorded when
+/// parsed, so that each may be accessed independently without parsing again.
 ///
 #[derive(Debug, Clone)]
-pub struct EmailAddress(String);
+pub struct EmailAddress {
+    address: String,
+    parts: Parts,
+}
+
+///
+/// The byte offsets of the components within an [EmailAddress]. The `local-part` extends from
+/// `local_start` to `separator`, the index of the `@`, and the `domain` from `domain_start` to
+/// `domain_end`.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Parts {
+    display_start: usize,
+    display_end: usize,
+    local_start: usize,
+    separator: usize,
+    domain_start: usize,
+    domain_end: usize,
+}
+
+///
+/// Receives each error found by the parser, with the component and the text within the address
+/// that it concerns. Returning the error stops parsing, as `parse_address` does, while returning
+/// `Ok` continues with the remaining checks, as `EmailAddress::check_all` does.
+///
+type Report<'a> = dyn FnMut(Error, Component, &str) -> Result<(), Error> + 'a;
 
 // ------------------------------------------------------------------------------------------------
 // Implementations
@@ -444,9 +737,7 @@ const LOCAL_PART_MAX_LENGTH: usize = 64;
 const DOMAIN_MAX_LENGTH: usize = 254;
 const SUB_DOMAIN_MAX_LENGTH: usize = 63;
 
-#[allow(dead_code)]
 const CR: char = '\r';
-#[allow(dead_code)]
 const LF: char = '\n';
 const SP: char = ' ';
 const HTAB: char = '\t';
@@ -457,54 +748,58 @@ const DOT: char = '.';
 const DQUOTE: char = '"';
 const LBRACKET: char = '[';
 const RBRACKET: char = ']';
-#[allow(dead_code)]
 const LPAREN: char = '(';
-#[allow(dead_code)]
 const RPAREN: char = ')';
 
-const DISPLAY_SEP: &str = " <";
+const IPV6_TAG: &str = "IPv6:";
+const IPV6_SEP: char = ':';
+
 const DISPLAY_START: char = '<';
 const DISPLAY_END: char = '>';
 
-const MAILTO_URI_PREFIX: &str = "mailto:";
-
 // ------------------------------------------------------------------------------------------------
 
 impl Display for Error {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.message())
+    }
+}
+
+impl Error {
+    ///
+    /// The message for each kind of error; this is a `const fn` so that it can be reported by a
+    /// failure at compile time, and so the length limits are written out rather than formatted.
+    ///
+    const fn message(&self) -> &'static str {
         match self {
-            Error::InvalidCharacter => write!(f, "Invalid character."),
-            Error::LocalPartEmpty => write!(f, "Local part is empty."),
-            Error::LocalPartTooLong => write!(
-                f,
-                "Local part is too long. Length limit: {}",
-                LOCAL_PART_MAX_LENGTH
-            ),
-            Error::DomainEmpty => write!(f, "Domain is empty."),
-            Error::DomainTooLong => {
-                write!(f, "Domain is too long. Length limit: {}", DOMAIN_MAX_LENGTH)
+            Error::InvalidCharacter => "Invalid character.",
+            Error::LocalPartEmpty => "Local part is empty.",
+            Error::LocalPartTooLong => "Local part is too long. Length limit: 64",
+            Error::DomainEmpty => "Domain is empty.",
+            Error::DomainTooLong => "Domain is too long. Length limit: 254",
+            Error::SubDomainEmpty => "A sub-domain is empty.",
+            Error::SubDomainTooLong => "A sub-domain is too long. Length limit: 63",
+            Error::MissingSeparator => "Missing separator character '@'.",
+            Error::DomainTooFew => "Too few parts in the domain",
+            Error::DomainInvalidSeparator => "Invalid placement of the domain separator ''.'",
+            Error::InvalidIPAddress => "Invalid IP Address specified for domain.",
+            Error::UnbalancedQuotes => "Quotes around the local-part are unbalanced.",
+            Error::InvalidComment => "A comment was badly formed.",
+            Error::UnsupportedDomainLiteral => "Domain literals are not supported.",
+            Error::UnsupportedDisplayName => "Display names are not supported.",
+            Error::MissingDisplayName => {
+                "Display name was not supplied, but email starts with '<'."
             }
-            Error::SubDomainEmpty => write!(f, "A sub-domain is empty."),
-            Error::SubDomainTooLong => write!(
-                f,
-                "A sub-domain is too long. Length limit: {}",
-                SUB_DOMAIN_MAX_LENGTH
-            ),
-            Error::MissingSeparator => write!(f, "Missing separator character '{}'.", AT),
-            Error::DomainTooFew => write!(f, "Too few parts in the domain"),
-            Error::DomainInvalidSeparator => {
-                write!(f, "Invalid placement of the domain separator '{:?}", DOT)
+            Error::MissingEndBracket => "Terminating '>' is missing.",
+            Error::InvalidGroup => "A group in the address list was badly formed.",
+            Error::AddressListEmpty => "The address list contains no addresses.",
+            Error::InvalidUri => "The mailto URI was badly formed.",
+            Error::InvalidALabel => "A sub-domain is not a valid IDNA A-label.",
+            Error::RequiresSmtpUtf8 => {
+                "The address contains non-ASCII characters and requires SMTPUTF8."
             }
-            Error::InvalidIPAddress => write!(f, "Invalid IP Address specified for domain."),
-            Error::UnbalancedQuotes => write!(f, "Quotes around the local-part are unbalanced."),
-            Error::InvalidComment => write!(f, "A comment was badly formed."),
-            Error::UnsupportedDomainLiteral => write!(f, "Domain literals are not supported."),
-            Error::UnsupportedDisplayName => write!(f, "Display names are not supported."),
-            Error::MissingDisplayName => write!(
-                f,
-                "Display name was not supplied, but email starts with '<'."
-            ),
-            Error::MissingEndBracket => write!(f, "Terminating '>' is missing."),
+            Error::InvalidPath => "The SMTP path was badly formed.",
+            Error::PathTooLong => "The SMTP path is too long. Length limit: 256",
         }
     }
 }
@@ -524,7 +819,12 @@ impl Default for Options {
         Self {
             minimum_sub_domains: Default::default(),
             allow_domain_literal: true,
+            allow_ipv4_literal: true,
+            allow_ipv6_literal: true,
+            allow_general_literal: false,
             allow_display_text: true,
+            allow_comments: false,
+            allow_smtputf8: true,
         }
     }
 }
@@ -571,6 +871,54 @@ impl Options {
             ..self
         }
     }
+    /// Set the value of `allow_ipv4_literal` to `true`.
+    #[inline(always)]
+    pub const fn with_ipv4_literal(self) -> Self {
+        Self {
+            allow_ipv4_literal: true,
+            ..self
+        }
+    }
+    /// Set the value of `allow_ipv4_literal` to `false`.
+    #[inline(always)]
+    pub const fn without_ipv4_literal(self) -> Self {
+        Self {
+            allow_ipv4_literal: false,
+            ..self
+        }
+    }
+    /// Set the value of `allow_ipv6_literal` to `true`.
+    #[inline(always)]
+    pub const fn with_ipv6_literal(self) -> Self {
+        Self {
+            allow_ipv6_literal: true,
+            ..self
+        }
+    }
+    /// Set the value of `allow_ipv6_literal` to `false`.
+    #[inline(always)]
+    pub const fn without_ipv6_literal(self) -> Self {
+        Self {
+            allow_ipv6_literal: false,
+            ..self
+        }
+    }
+    /// Set the value of `allow_general_literal` to `true`.
+    #[inline(always)]
+    pub const fn with_general_literal(self) -> Self {
+        Self {
+            allow_general_literal: true,
+            ..self
+        }
+    }
+    /// Set the value of `allow_general_literal` to `false`.
+    #[inline(always)]
+    pub const fn without_general_literal(self) -> Self {
+        Self {
+            allow_general_literal: false,
+            ..self
+        }
+    }
     /// Set the value of `allow_display_text` to `true`.
     #[inline(always)]
     pub const fn with_display_text(self) -> Self {
@@ -587,13 +935,91 @@ impl Options {
             ..self
         }
     }
+    /// Set the value of `allow_comments` to `true`.
+    #[inline(always)]
+    pub const fn with_comments(self) -> Self {
+        Self {
+            allow_comments: true,
+            ..self
+        }
+    }
+    /// Set the value of `allow_comments` to `false`.
+    #[inline(always)]
+    pub const fn without_comments(self) -> Self {
+        Self {
+            allow_comments: false,
+            ..self
+        }
+    }
+    /// Set the value of `allow_smtputf8` to `true`.
+    #[inline(always)]
+    pub const fn with_smtputf8(self) -> Self {
+        Self {
+            allow_smtputf8: true,
+            ..self
+        }
+    }
+    /// Set the value of `allow_smtputf8` to `false`.
+    #[inline(always)]
+    pub const fn without_smtputf8(self) -> Self {
+        Self {
+            allow_smtputf8: false,
+            ..self
+        }
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl Display for Host<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Host::Domain(domain) => write!(f, "{}", domain),
+            Host::Ipv4(address) => write!(f, "{}{}{}", LBRACKET, address, RBRACKET),
+            Host::Ipv6(address) => write!(f, "{}{}{}{}", LBRACKET, IPV6_TAG, address, RBRACKET),
+            Host::General { tag, content } => {
+                write!(f, "{}{}{}{}{}", LBRACKET, tag, IPV6_SEP, content, RBRACKET)
+            }
+        }
+    }
+}
+
+impl Host<'_> {
+    ///
+    /// Returns `true` if this host is a domain name rather than an address literal.
+    ///
+    pub fn is_domain(&self) -> bool {
+        matches!(self, Host::Domain(_))
+    }
+
+    ///
+    /// Returns the IP address if this host is either an IPv4 or IPv6 address literal.
+    ///
+    /// ```rust
+    /// use email_address::*;
+    /// use std::net::{IpAddr, Ipv4Addr};
+    /// use std::str::FromStr;
+    ///
+    /// assert_eq!(
+    ///     EmailAddress::from_str("jsmith@[192.168.2.1]").unwrap().host().ip_addr(),
+    ///     Some(IpAddr::V4(Ipv4Addr::new(192, 168, 2, 1)))
+    /// );
+    /// ```
+    ///
+    pub fn ip_addr(&self) -> Option<IpAddr> {
+        match self {
+            Host::Ipv4(address) => Some(IpAddr::V4(*address)),
+            Host::Ipv6(address) => Some(IpAddr::V6(*address)),
+            _ => None,
+        }
+    }
 }
 
 // ------------------------------------------------------------------------------------------------
 
 impl Display for EmailAddress {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", self.0)
+        write!(f, "{}", self.address)
     }
 }
 
@@ -606,12 +1032,15 @@ impl Display for EmailAddress {
 // local-parts impedes interoperability and is discouraged. Mailbox domains
 // follow normal DNS rules and are hence not case sensitive.
 //
+// Equality, hashing, and ordering are all implemented by `EmailAddressRef`, so
+// that equal addresses always have equal hashes. These use the value of each
+// component, ignoring the display name and any comments, removing the quotes
+// from a quoted local-part, and case-folding the domain.
+//
 
 impl PartialEq for EmailAddress {
     fn eq(&self, other: &Self) -> bool {
-        let (left, right) = split_at(&self.0).unwrap();
-        let (other_left, other_right) = split_at(&other.0).unwrap();
-        left.eq(other_left) && right.eq_ignore_ascii_case(other_right)
+        self.as_email_ref() == other.as_email_ref()
     }
 }
 
@@ -619,10 +1048,62 @@ impl Eq for EmailAddress {}
 
 impl Hash for EmailAddress {
     fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
-        self.0.hash(state);
+        self.as_email_ref().hash(state);
+    }
+}
+
+impl PartialOrd for EmailAddress {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for EmailAddress {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.as_email_ref().cmp(&other.as_email_ref())
+    }
+}
+
+impl Parts {
+    ///
+    /// Record the boundaries of the components of `address`. If `address` cannot be split the
+    /// entire string is treated as the `local-part`, with an empty `domain`.
+    ///
+    fn new(address: &str) -> Self {
+        match split_parts(address) {
+            Ok((local, domain, display)) => Self::from_components(address, display, local, domain),
+            Err(_) => Self {
+                display_start: 0,
+                display_end: 0,
+                local_start: 0,
+                separator: address.len(),
+                domain_start: address.len(),
+                domain_end: address.len(),
+            },
+        }
+    }
+
+    ///
+    /// Record the boundaries of the components of `address`, as split by the parser, each of
+    /// which must be a sub-slice of `address`.
+    ///
+    fn from_components(address: &str, display: &str, local: &str, domain: &str) -> Self {
+        let display = span_of(address, display);
+        let local = span_of(address, local);
+        let domain = span_of(address, domain);
+        Self {
+            display_start: display.start,
+            display_end: display.end,
+            local_start: local.start,
+            separator: local.end,
+            domain_start: domain.start,
+            domain_end: domain.end,
+        }
     }
 }
 
+// ------------------------------------------------------------------------------------------------
+
 impl FromStr for EmailAddress {
     type Err = Error;
 
@@ -633,13 +1114,13 @@ impl FromStr for EmailAddress {
 
 impl From<EmailAddress> for String {
     fn from(email: EmailAddress) -> Self {
-        email.0
+        email.address
     }
 }
 
 impl AsRef<str> for EmailAddress {
     fn as_ref(&self) -> &str {
-        &self.0
+        &self.address
     }
 }
 
@@ -649,7 +1130,7 @@ impl Serialize for EmailAddress {
     where
         S: Serializer,
     {
-        serializer.serialize_str(&self.0)
+        serializer.serialize_str(&self.address)
     }
 }
 
@@ -705,7 +1186,9 @@ impl EmailAddress {
     where
         S: Into<String>,
     {
-        Self(address.into())
+        let address = address.into();
+        let parts = Parts::new(&address);
+        Self { address, parts }
     }
 
     ///
@@ -725,23 +1208,62 @@ impl EmailAddress {
     }
 
     ///
-    /// Determine whether the `address` string is a valid email address. Note this is equivalent to
-    /// the following:
+    /// Parses an [EmailAddress] with custom [Options], as `parse_with_options`, however any
+    /// error is returned as a [ParseError] which identifies the location, offending character,
+    /// and component of the address in error.
     ///
     /// ```rust
     /// use email_address::*;
-    /// use std::str::FromStr;
     ///
-    /// let is_valid = EmailAddress::from_str("johnstonskj@gmail.com").is_ok();
-    /// ```
+    /// let error = EmailAddress::parse_detailed("John <john.doe@exam,ple.com>", Options::default())
+    ///     .unwrap_err();
     ///
-    pub fn is_valid(address: &str) -> bool {
+    /// assert_eq!(error.kind, Error::InvalidCharacter);
+    /// assert_eq!(error.span, 19..20);
+    /// assert_eq!(error.found, Some(','));
+    /// assert_eq!(error.component, Component::DomainLabel(0));
+    /// ```
+    pub fn parse_detailed(address: &str, options: Options) -> Result<Self, ParseError> {
+        parse_address_detailed(address, options)
+    }
+
+    ///
+    /// Check the `address` string with custom [Options], returning every problem found rather
+    /// than only the first. The address is valid if, and only if, the result is empty.
+    ///
+    /// ```rust
+    /// use email_address::*;
+    ///
+    /// let findings = EmailAddress::check_all("sim,on@example..com", Options::default());
+    ///
+    /// assert_eq!(findings.len(), 2);
+    /// assert_eq!(findings[0].error.kind, Error::InvalidCharacter);
+    /// assert_eq!(findings[0].error.component, Component::LocalPart);
+    /// assert_eq!(findings[1].error.kind, Error::SubDomainEmpty);
+    /// assert_eq!(findings[1].error.component, Component::DomainLabel(1));
+    /// ```
+    pub fn check_all(address: &str, options: Options) -> Vec<Finding> {
+        check_address(address, options)
+    }
+
+    ///
+    /// Determine whether the `address` string is a valid email address. Note this is equivalent to
+    /// the following:
+    ///
+    /// ```rust
+    /// use email_address::*;
+    /// use std::str::FromStr;
+    ///
+    /// let is_valid = EmailAddress::from_str("johnstonskj@gmail.com").is_ok();
+    /// ```
+    ///
+    pub fn is_valid(address: &str) -> bool {
         Self::from_str(address).is_ok()
     }
 
     ///
     /// Determine whether the `part` string would be a valid `local-part` if it were in an
-    /// email address.
+    /// email address. This is equivalent to `LocalPart::from_str(part).is_ok()`.
     ///
     pub fn is_valid_local_part(part: &str) -> bool {
         parse_local_part(part, Default::default()).is_ok()
@@ -749,7 +1271,7 @@ impl EmailAddress {
 
     ///
     /// Determine whether the `part` string would be a valid `domain` if it were in an
-    /// email address.
+    /// email address. This is equivalent to `Domain::from_str(part).is_ok()`.
     ///
     pub fn is_valid_domain(part: &str) -> bool {
         parse_domain(part, Default::default()).is_ok()
@@ -757,7 +1279,9 @@ impl EmailAddress {
 
     ///
     /// Return this email address formatted as a URI. This will also URI-encode the email
-    /// address itself. So, `name@example.org` becomes `mailto:name@example.org`.
+    /// address itself. So, `name@example.org` becomes `mailto:name@example.org`. Only the
+    /// email part is included, any display part and comments are removed. To add header
+    /// fields such as `subject` use [MailtoUri].
     ///
     /// ```rust
     /// use email_address::*;
@@ -770,8 +1294,30 @@ impl EmailAddress {
     /// ```
     ///
     pub fn to_uri(&self) -> String {
-        let encoded = encode(&self.0);
-        format!("{}{}", MAILTO_URI_PREFIX, encoded)
+        MailtoUri::new(self.clone()).to_string()
+    }
+
+    ///
+    /// Parse the first recipient address from a `mailto` URI, as produced by `to_uri`. To
+    /// access all recipients, and any header fields, use [MailtoUri].
+    ///
+    /// ```rust
+    /// use email_address::*;
+    /// use std::str::FromStr;
+    ///
+    /// let email = EmailAddress::from_str("用户@例子.广告").unwrap();
+    ///
+    /// assert_eq!(EmailAddress::from_uri(&email.to_uri()), Ok(email));
+    /// assert_eq!(EmailAddress::from_uri("mailto:"), Err(Error::MissingSeparator));
+    /// assert_eq!(EmailAddress::from_uri("http://example.com"), Err(Error::InvalidUri));
+    /// ```
+    ///
+    pub fn from_uri(uri: &str) -> Result<Self, Error> {
+        let uri = MailtoUri::from_str(uri)?;
+        match uri.to().first() {
+            None => Error::MissingSeparator.into(),
+            Some(address) => Ok(address.clone()),
+        }
     }
 
     ///
@@ -789,8 +1335,59 @@ impl EmailAddress {
     /// );
     /// ```
     ///
+    /// The display name is written as an RFC 5322 `phrase`, if it is not a sequence of atoms
+    /// separated by single spaces it is quoted, with any `"` and `\` characters escaped. Control
+    /// characters, which cannot appear in a header, are replaced with spaces. If the display
+    /// name is empty only the email part is returned.
+    ///
+    /// ```rust
+    /// use email_address::*;
+    /// use std::str::FromStr;
+    ///
+    /// let email = EmailAddress::from_str("john@example.org").unwrap();
+    ///
+    /// assert_eq!(
+    ///     email.to_display("Doe, John \"Jack\""),
+    ///     String::from("\"Doe, John \\\"Jack\\\"\" <john@example.org>")
+    /// );
+    /// assert_eq!(email.to_display(""), String::from("john@example.org"));
+    /// ```
+    ///
     pub fn to_display(&self, display_name: &str) -> String {
-        format!("{} <{}>", display_name, self)
+        self.format_display(encode_display_name(display_name, false))
+    }
+
+    ///
+    /// Return a string formatted as a display email with the user name, as `to_display`, except
+    /// that a display name containing non-ASCII characters is written as RFC 2047 encoded-words.
+    /// This is required for transports that do not support SMTPUTF8 or RFC 6532 headers.
+    ///
+    /// ```rust
+    /// use email_address::*;
+    /// use std::str::FromStr;
+    ///
+    /// assert_eq!(
+    ///     EmailAddress::from_str("andre@example.org").unwrap().to_encoded_display("André Pirard"),
+    ///     String::from("=?UTF-8?Q?Andr=C3=A9_Pirard?= <andre@example.org>")
+    /// );
+    /// ```
+    ///
+    pub fn to_encoded_display(&self, display_name: &str) -> String {
+        self.format_display(encode_display_name(display_name, true))
+    }
+
+    fn format_display(&self, display_name: Cow<'_, str>) -> String {
+        if display_name.is_empty() {
+            self.email().to_string()
+        } else {
+            format!(
+                "{} {}{}{}",
+                display_name,
+                DISPLAY_START,
+                self.email(),
+                DISPLAY_END
+            )
+        }
     }
 
     ///
@@ -808,8 +1405,46 @@ impl EmailAddress {
     /// ```
     ///
     pub fn local_part(&self) -> &str {
-        let (local, _, _) = split_parts(&self.0).unwrap();
-        local
+        self.as_email_ref().local_part()
+    }
+
+    ///
+    /// Returns the value of the local part, as used to look up the mailbox, with any comments
+    /// and the quotes around a quoted local part removed, and each quoted-pair replaced by the
+    /// character it quotes. The result is borrowed unless a quoted-pair or comment is removed.
+    ///
+    /// ```rust
+    /// use email_address::*;
+    /// use std::str::FromStr;
+    ///
+    /// let email = EmailAddress::from_str("\"Joe.\\\\Blow\"@example.com").unwrap();
+    ///
+    /// assert_eq!(email.local_part(), "\"Joe.\\\\Blow\"");
+    /// assert_eq!(email.local_part_unquoted(), "Joe.\\Blow");
+    /// ```
+    ///
+    pub fn local_part_unquoted(&self) -> Cow<'_, str> {
+        self.as_email_ref().local_part_unquoted()
+    }
+
+    ///
+    /// Returns the minimal form of a local part with the value `value`; this is `value`
+    /// itself if it is a valid `dot-atom`, otherwise a quoted string in which only the `"` and
+    /// `\` characters are written as quoted-pairs. This is the reverse of `local_part_unquoted`.
+    /// Some characters, such as controls, cannot appear in a local part even when quoted, so the
+    /// result should be validated, for example with `is_valid_local_part`.
+    ///
+    /// ```rust
+    /// use email_address::*;
+    ///
+    /// assert_eq!(EmailAddress::quote_local_part("joe.blow"), "joe.blow");
+    /// assert_eq!(EmailAddress::quote_local_part("joe blow"), "\"joe blow\"");
+    /// assert_eq!(EmailAddress::quote_local_part("Joe.\\Blow"), "\"Joe.\\\\Blow\"");
+    /// assert_eq!(EmailAddress::quote_local_part("joe..blow"), "\"joe..blow\"");
+    /// ```
+    ///
+    pub fn quote_local_part(value: &str) -> Cow<'_, str> {
+        local_part_from_value(value)
     }
 
     ///
@@ -827,8 +1462,28 @@ impl EmailAddress {
     /// ```
     ///
     pub fn display_part(&self) -> &str {
-        let (_, _, display) = split_parts(&self.0).unwrap();
-        display
+        self.as_email_ref().display_part()
+    }
+
+    ///
+    /// Returns the display name of the email address, decoded from the raw `display_part`.
+    /// The display part is parsed as an RFC 5322 `phrase`: quotes are removed, quoted-pairs
+    /// are resolved, comments are removed, and any RFC 2047 encoded-words are decoded.
+    ///
+    /// ```rust
+    /// use email_address::*;
+    /// use std::str::FromStr;
+    ///
+    /// let email = EmailAddress::from_str("\"Doe, John\" <john@example.org>").unwrap();
+    /// assert_eq!(email.display_part(), "\"Doe, John\"");
+    /// assert_eq!(email.display_name(), "Doe, John");
+    ///
+    /// let email = EmailAddress::from_str("=?UTF-8?B?S8O2bmln?= <koenig@example.org>").unwrap();
+    /// assert_eq!(email.display_name(), "König");
+    /// ```
+    ///
+    pub fn display_name(&self) -> Cow<'_, str> {
+        decode_display_name(self.display_part())
     }
 
     ///
@@ -841,13 +1496,12 @@ impl EmailAddress {
     ///
     /// assert_eq!(
     ///     EmailAddress::from_str("Name <name@example.org>").unwrap().email(),
-    ///     String::from("name@example.org")
+    ///     "name@example.org"
     /// );
     /// ```
     ///
-    pub fn email(&self) -> String {
-        let (local, domain, _) = split_parts(&self.0).unwrap();
-        format!("{}{AT}{}", local, domain)
+    pub fn email(&self) -> &str {
+        self.as_email_ref().email()
     }
 
     ///
@@ -865,234 +1519,1156 @@ impl EmailAddress {
     /// ```
     ///
     pub fn domain(&self) -> &str {
-        let (_, domain, _) = split_parts(&self.0).unwrap();
-        domain
+        self.as_email_ref().domain()
     }
 
     ///
-    /// Returns the entire email address as a string reference.
+    /// Returns the domain of the email address in its ASCII form, as required by servers that
+    /// do not support SMTPUTF8. Each U-label in the domain is converted to an A-label, and any
+    /// existing A-labels are validated. The UTS #46 processing rules are only followed in full
+    /// with the `idna_support` feature. Domain literals and comments are not changed.
     ///
-    pub fn as_str(&self) -> &str {
-        self.as_ref()
-    }
-}
-
-// ------------------------------------------------------------------------------------------------
-// Private Functions
-// ------------------------------------------------------------------------------------------------
-
-fn encode(address: &str) -> String {
-    let mut result = String::new();
-    for c in address.chars() {
-        if is_uri_reserved(c) {
-            result.push_str(&format!("%{:02X}", c as u8))
+    /// ```rust
+    /// use email_address::*;
+    /// use std::str::FromStr;
+    ///
+    /// assert_eq!(
+    ///     EmailAddress::from_str("用户@例子.广告").unwrap().to_ascii_domain(),
+    ///     Ok(String::from("xn--fsqu00a.xn--4rr70v"))
+    /// );
+    /// ```
+    ///
+    pub fn to_ascii_domain(&self) -> Result<String, Error> {
+        if self.host().is_domain() {
+            domain_to_ascii(&strip_cfws(self.domain())?)
         } else {
-            result.push(c);
+            Ok(self.domain().to_string())
         }
     }
-    result
-}
-
-fn is_uri_reserved(c: char) -> bool {
-    // No need to encode '@' as this is allowed in the email scheme.
-    c == '!'
-        || c == '#'
-        || c == '$'
-        || c == '%'
-        || c == '&'
-        || c == '\''
-        || c == '('
-        || c == ')'
-        || c == '*'
-        || c == '+'
-        || c == ','
-        || c == '/'
-        || c == ':'
-        || c == ';'
-        || c == '='
-        || c == '?'
-        || c == '['
-        || c == ']'
-}
 
-fn parse_address(address: &str, options: Options) -> Result<EmailAddress, Error> {
-    //
-    // Deals with cases of '@' in `local-part`, if it is quoted they are legal, if
-    // not then they'll return an `InvalidCharacter` error later.
-    //
-    let (local_part, domain, display) = split_parts(address)?;
-    match (
-        display.is_empty(),
-        local_part.starts_with(DISPLAY_START),
-        options.allow_display_text,
-    ) {
-        (false, _, false) => Err(Error::UnsupportedDisplayName),
-        (true, true, true) => Err(Error::MissingDisplayName),
-        (true, true, false) => Err(Error::InvalidCharacter),
-        _ => {
-            parse_local_part(local_part, options)?;
-            parse_domain(domain, options)?;
-            Ok(EmailAddress(address.to_owned()))
+    ///
+    /// Returns the domain of the email address in its Unicode form, each A-label in the domain
+    /// is validated and converted to a U-label. The UTS #46 processing rules are only followed
+    /// in full with the `idna_support` feature. Domain literals and comments are not changed.
+    ///
+    /// ```rust
+    /// use email_address::*;
+    /// use std::str::FromStr;
+    ///
+    /// assert_eq!(
+    ///     EmailAddress::from_str("user@xn--bcher-kva.example").unwrap().to_unicode_domain(),
+    ///     Ok(String::from("bücher.example"))
+    /// );
+    /// ```
+    ///
+    pub fn to_unicode_domain(&self) -> Result<String, Error> {
+        if self.host().is_domain() {
+            domain_to_unicode(&strip_cfws(self.domain())?)
+        } else {
+            Ok(self.domain().to_string())
         }
     }
-}
-
-fn split_parts(address: &str) -> Result<(&str, &str, &str), Error> {
-    let (display, email) = split_display_email(address)?;
-    let (local_part, domain) = split_at(email)?;
-    Ok((local_part, domain, display))
-}
 
-fn split_display_email(text: &str) -> Result<(&str, &str), Error> {
-    match text.rsplit_once(DISPLAY_SEP) {
-        None => Ok(("", text)),
-        Some((left, right)) => {
-            let right = right.trim();
-            if !right.ends_with(DISPLAY_END) {
-                Err(Error::MissingEndBracket)
-            } else {
-                let email = &right[0..right.len() - 1];
-                let display_name = left.trim();
-
-                Ok((display_name, email))
-            }
-        }
+    ///
+    /// Returns the [Diagnosis] category of this address, which determines whether it may be
+    /// used unchanged in the SMTP envelope, or only as RFC 5322 message header syntax.
+    ///
+    /// ```rust
+    /// use email_address::*;
+    /// use std::str::FromStr;
+    ///
+    /// assert_eq!(
+    ///     EmailAddress::from_str("simon@example.com").unwrap().diagnose(),
+    ///     Diagnosis::Valid
+    /// );
+    /// assert_eq!(
+    ///     EmailAddress::from_str("simon@[127.0.0.1]").unwrap().diagnose(),
+    ///     Diagnosis::Rfc5321
+    /// );
+    /// assert_eq!(
+    ///     EmailAddress::parse_with_options("simon(work)@example.com", Options::default().with_comments())
+    ///         .unwrap()
+    ///         .diagnose(),
+    ///     Diagnosis::Deprecated
+    /// );
+    /// ```
+    ///
+    pub fn diagnose(&self) -> Diagnosis {
+        diagnose_address(self)
     }
-}
 
-fn split_at(address: &str) -> Result<(&str, &str), Error> {
-    match address.rsplit_once(AT) {
-        None => Error::MissingSeparator.into(),
-        Some(left_right) => Ok(left_right),
+    ///
+    /// Returns the domain of the email address parsed into a [Host], so that domain literals
+    /// can be used as IP addresses without re-parsing. Any comments around a domain literal are
+    /// ignored, a domain name is returned as-is from `domain`.
+    ///
+    /// ```rust
+    /// use email_address::*;
+    /// use std::net::Ipv6Addr;
+    /// use std::str::FromStr;
+    ///
+    /// assert_eq!(
+    ///     EmailAddress::from_str("name@example.org").unwrap().host(),
+    ///     Host::Domain("example.org")
+    /// );
+    /// assert_eq!(
+    ///     EmailAddress::from_str("name@[IPv6:2001:db8::1]").unwrap().host(),
+    ///     Host::Ipv6(Ipv6Addr::new(0x2001, 0xdb8, 0, 0, 0, 0, 0, 1))
+    /// );
+    /// ```
+    ///
+    pub fn host(&self) -> Host<'_> {
+        self.as_email_ref().host()
     }
-}
 
-fn parse_local_part(part: &str, _: Options) -> Result<(), Error> {
-    if part.is_empty() {
-        Error::LocalPartEmpty.into()
-    } else if part.len() > LOCAL_PART_MAX_LENGTH {
-        Error::LocalPartTooLong.into()
-    } else if part.starts_with(DQUOTE) && part.ends_with(DQUOTE) {
-        // <= to handle `part` = `"` (single quote).
-        if part.len() <= 2 {
-            Error::LocalPartEmpty.into()
+    ///
+    /// Returns a copy of this email address with any comments and folding white space removed
+    /// from the `local-part` and `domain`. The display part, if present, is retained.
+    ///
+    /// ```rust
+    /// use email_address::*;
+    ///
+    /// let email = EmailAddress::parse_with_options(
+    ///     "john(work)@example.com (John)",
+    ///     Options::default().with_comments(),
+    /// ).unwrap();
+    ///
+    /// assert_eq!(email.local_part(), "john(work)");
+    /// assert_eq!(email.strip_comments().as_str(), "john@example.com");
+    /// ```
+    ///
+    pub fn strip_comments(&self) -> Self {
+        let (local, domain, display) = (self.local_part(), self.domain(), self.display_part());
+        let local = strip_cfws(local).unwrap_or(Cow::Borrowed(local));
+        let domain = strip_cfws(domain).unwrap_or(Cow::Borrowed(domain));
+        if display.is_empty() {
+            Self::new_unchecked(format!("{}{}{}", local, AT, domain))
         } else {
-            parse_quoted_local_part(&part[1..part.len() - 1])
+            Self::new_unchecked(format!(
+                "{} {}{}{}{}{}",
+                display, DISPLAY_START, local, AT, domain, DISPLAY_END
+            ))
         }
-    } else {
-        parse_unquoted_local_part(part)
-    }
-}
-
-fn parse_quoted_local_part(part: &str) -> Result<(), Error> {
-    if is_qcontent(part) {
-        Ok(())
-    } else {
-        Error::InvalidCharacter.into()
     }
-}
 
-fn parse_unquoted_local_part(part: &str) -> Result<(), Error> {
-    if is_dot_atom_text(part) {
-        Ok(())
-    } else {
-        Error::InvalidCharacter.into()
+    ///
+    /// Returns the entire email address as a string reference.
+    ///
+    pub fn as_str(&self) -> &str {
+        self.as_ref()
     }
-}
 
-fn parse_domain(part: &str, options: Options) -> Result<(), Error> {
-    if part.is_empty() {
-        Error::DomainEmpty.into()
-    } else if part.len() > DOMAIN_MAX_LENGTH {
-        Error::DomainTooLong.into()
-    } else if part.starts_with(LBRACKET) && part.ends_with(RBRACKET) {
-        if options.allow_domain_literal {
-            parse_literal_domain(&part[1..part.len() - 1])
-        } else {
-            Error::UnsupportedDomainLiteral.into()
-        }
-    } else {
-        parse_text_domain(part, options)
+    ///
+    /// Returns this email address borrowed as an [EmailAddressRef], for use with APIs that
+    /// accept borrowed addresses.
+    ///
+    /// ```rust
+    /// use email_address::*;
+    /// use std::str::FromStr;
+    ///
+    /// let email = EmailAddress::from_str("simon@example.com").unwrap();
+    /// let borrowed = email.as_email_ref();
+    ///
+    /// assert_eq!(borrowed.domain(), "example.com");
+    /// assert_eq!(borrowed.to_owned(), email);
+    /// ```
+    ///
+    pub fn as_email_ref(&self) -> EmailAddressRef<'_> {
+        EmailAddressRef::from_parts(&self.address, self.parts)
     }
-}
-
-fn parse_text_domain(part: &str, options: Options) -> Result<(), Error> {
-    let mut sub_domains = 0;
-
-    for sub_part in part.split(DOT) {
-        // As per https://www.rfc-editor.org/rfc/rfc1034#section-3.5
-        // and https://html.spec.whatwg.org/multipage/input.html#valid-e-mail-address,
-        // at least one character must exist in a `subdomain`/`label` part of the domain
-        if sub_part.is_empty() {
-            return Error::SubDomainEmpty.into();
-        }
-
-        // As per https://www.rfc-editor.org/rfc/rfc1034#section-3.5,
-        // the domain label needs to start with a `letter`;
-        // however, https://html.spec.whatwg.org/multipage/input.html#valid-e-mail-address
-        // specifies a label can start
-        // with a `let-dig` (letter or digit), so we allow the wider range
-
-        if !sub_part.starts_with(char::is_alphanumeric) {
-            return Error::InvalidCharacter.into();
-        }
-        // Both specifications mentioned above require the last character to be a
-        // `let-dig` (letter or digit)
-        if !sub_part.ends_with(char::is_alphanumeric) {
-            return Error::InvalidCharacter.into();
-        }
 
-        if sub_part.len() > SUB_DOMAIN_MAX_LENGTH {
-            return Error::SubDomainTooLong.into();
-        }
-
-        if !is_atom(sub_part) {
-            return Error::InvalidCharacter.into();
-        }
-
-        sub_domains += 1;
+    ///
+    /// Returns a new address in a normalized form, each step of which is configured by
+    /// [NormalizeOptions]. Comments are always removed, and the result is parsed again to
+    /// ensure it is a valid address. An error is returned if the domain cannot be converted to
+    /// the requested [IdnaForm].
+    ///
+    /// ```rust
+    /// use email_address::*;
+    /// use std::str::FromStr;
+    ///
+    /// let email = EmailAddress::from_str("John <\"john\"@Example.COM>").unwrap();
+    ///
+    /// assert_eq!(
+    ///     email.normalize(NormalizeOptions::default().with_display_name()).unwrap().as_str(),
+    ///     "John <john@example.com>"
+    /// );
+    /// ```
+    ///
+    pub fn normalize(&self, options: NormalizeOptions) -> Result<Self, Error> {
+        normalize_address(self, options)
     }
 
-    if sub_domains < options.minimum_sub_domains {
-        Error::DomainTooFew.into()
-    } else {
-        Ok(())
+    ///
+    /// Returns the canonical form of this address, that is `normalize` with the default
+    /// [NormalizeOptions]. The display name is removed, the domain is lowercased and written
+    /// as A-labels, the `local-part` uses the minimum quoting possible and is in Unicode
+    /// Normalization Form C.
+    ///
+    /// ```rust
+    /// use email_address::*;
+    /// use std::str::FromStr;
+    ///
+    /// let email = EmailAddress::from_str("John <\"john\"@Bücher.Example>").unwrap();
+    ///
+    /// assert_eq!(
+    ///     email.canonical().unwrap().as_str(),
+    ///     "john@xn--bcher-kva.example"
+    /// );
+    /// ```
+    ///
+    pub fn canonical(&self) -> Result<Self, Error> {
+        self.normalize(NormalizeOptions::default())
     }
-}
 
-fn parse_literal_domain(part: &str) -> Result<(), Error> {
-    if part.chars().all(is_dtext_char) {
-        return Ok(());
-    }
-    Error::InvalidCharacter.into()
+    ///
+    /// Compare the domains of two addresses label by label, starting with the top-level domain,
+    /// so that sorting groups subdomains together under their parent domains. Domains are
+    /// compared case-insensitively, and a domain literal is compared as a single label.
+    ///
+    /// ```rust
+    /// use email_address::*;
+    /// use std::cmp::Ordering;
+    /// use std::str::FromStr;
+    ///
+    /// let simon = EmailAddress::from_str("simon@mail.example.com").unwrap();
+    /// let john = EmailAddress::from_str("john@Example.com").unwrap();
+    /// let jane = EmailAddress::from_str("jane@example.org").unwrap();
+    ///
+    /// assert_eq!(simon.cmp_domains(&john), Ordering::Greater);
+    /// assert_eq!(john.cmp_domains(&jane), Ordering::Less);
+    /// ```
+    ///
+    pub fn cmp_domains(&self, other: &Self) -> Ordering {
+        let domain = self.as_email_ref().folded_domain();
+        let other_domain = other.as_email_ref().folded_domain();
+        reversed_labels(&domain).cmp(reversed_labels(&other_domain))
+    }
+
+    ///
+    /// Compare two addresses by domain first, as `cmp_domains`, and then by `local-part`. This
+    /// is useful to group a list of recipients by domain, or mail exchanger, with a stable
+    /// order within each group. Addresses that compare `Equal` are equal according to `Eq`.
+    ///
+    /// ```rust
+    /// use email_address::*;
+    /// use std::str::FromStr;
+    ///
+    /// let mut recipients: Vec<EmailAddress> = [
+    ///     "zoe@example.org",
+    ///     "simon@mail.example.com",
+    ///     "john@example.com",
+    ///     "amy@example.org",
+    /// ]
+    /// .iter()
+    /// .map(|s| EmailAddress::from_str(s).unwrap())
+    /// .collect();
+    ///
+    /// recipients.sort_by(EmailAddress::cmp_by_domain);
+    ///
+    /// assert_eq!(
+    ///     recipients.iter().map(EmailAddress::as_str).collect::<Vec<_>>(),
+    ///     vec![
+    ///         "john@example.com",
+    ///         "simon@mail.example.com",
+    ///         "amy@example.org",
+    ///         "zoe@example.org",
+    ///     ]
+    /// );
+    /// ```
+    ///
+    pub fn cmp_by_domain(&self, other: &Self) -> Ordering {
+        self.cmp_domains(other)
+            .then_with(|| cmp_local_part_values(self.local_part(), other.local_part()))
+    }
+
+    ///
+    /// Split the `local-part` into the base and detail of an RFC 5233 subaddress, at the first
+    /// occurrence of `separator`. Comments and the quotes around a quoted `local-part` are
+    /// removed before splitting, and the detail may be empty. If `separator` is not present the
+    /// base is the entire `local-part` and no detail is returned.
+    ///
+    /// ```rust
+    /// use email_address::*;
+    /// use std::str::FromStr;
+    ///
+    /// let email = EmailAddress::from_str("user.name+tag+sorting@example.com").unwrap();
+    ///
+    /// let (base, detail) = email.split_subaddress('+');
+    /// assert_eq!(base, "user.name");
+    /// assert_eq!(detail.unwrap(), "tag+sorting");
+    ///
+    /// let (base, detail) = email.split_subaddress('-');
+    /// assert_eq!(base, "user.name+tag+sorting");
+    /// assert!(detail.is_none());
+    /// ```
+    ///
+    pub fn split_subaddress(&self, separator: char) -> (Cow<'_, str>, Option<Cow<'_, str>>) {
+        self.as_email_ref().split_subaddress(separator)
+    }
+
+    ///
+    /// Returns the detail of an RFC 5233 subaddress, as `split_subaddress`, if present.
+    ///
+    /// ```rust
+    /// use email_address::*;
+    /// use std::str::FromStr;
+    ///
+    /// let email = EmailAddress::from_str("user-bulk@example.com").unwrap();
+    ///
+    /// assert_eq!(email.subaddress('-').unwrap(), "bulk");
+    /// assert!(email.subaddress('+').is_none());
+    /// ```
+    ///
+    pub fn subaddress(&self, separator: char) -> Option<Cow<'_, str>> {
+        self.split_subaddress(separator).1
+    }
+
+    ///
+    /// Returns a new address with any subaddress detail, and the `separator` preceding it,
+    /// removed from the `local-part`. The display part and domain are unchanged. If the
+    /// `local-part` is only a subaddress, such as `+tag@example.com`, removing it would leave
+    /// the `local-part` empty and so the address is returned unchanged.
+    ///
+    /// ```rust
+    /// use email_address::*;
+    /// use std::str::FromStr;
+    ///
+    /// let email = EmailAddress::from_str("Simon <simon+lists@example.com>").unwrap();
+    ///
+    /// assert_eq!(
+    ///     email.without_subaddress('+').unwrap().as_str(),
+    ///     "Simon <simon@example.com>"
+    /// );
+    /// ```
+    ///
+    pub fn without_subaddress(&self, separator: char) -> Result<Self, Error> {
+        let (base, _) = self.split_subaddress(separator);
+        if base.is_empty() {
+            return Ok(self.clone());
+        }
+        build_address(
+            self.display_part(),
+            &local_part_from_value(&base),
+            self.domain(),
+        )
+    }
+
+    ///
+    /// Returns a new address with the subaddress `detail`, and the `separator` preceding it,
+    /// added to the base of the `local-part`, replacing any existing detail. The `local-part`
+    /// is quoted if required, and an error is returned if the result is not a valid address.
+    ///
+    /// ```rust
+    /// use email_address::*;
+    /// use std::str::FromStr;
+    ///
+    /// let email = EmailAddress::from_str("simon+lists@example.com").unwrap();
+    ///
+    /// assert_eq!(
+    ///     email.with_subaddress('+', "bills").unwrap().as_str(),
+    ///     "simon+bills@example.com"
+    /// );
+    /// assert_eq!(
+    ///     email.with_subaddress('+', "my bills").unwrap().as_str(),
+    ///     "\"simon+my bills\"@example.com"
+    /// );
+    /// ```
+    ///
+    pub fn with_subaddress(&self, separator: char, detail: &str) -> Result<Self, Error> {
+        let (base, _) = self.split_subaddress(separator);
+        let value = format!("{}{}{}", base, separator, detail);
+        build_address(
+            self.display_part(),
+            &local_part_from_value(&value),
+            self.domain(),
+        )
+    }
+
+    ///
+    /// Returns a copy of this address with the `local-part` replaced by `local_part`, which is
+    /// the unescaped value and is quoted if required, as by [EmailAddressBuilder]. An error is
+    /// returned if the result is not a valid address.
+    ///
+    /// ```rust
+    /// use email_address::*;
+    /// use std::str::FromStr;
+    ///
+    /// let email = EmailAddress::from_str("Simon <simon@example.com>").unwrap();
+    ///
+    /// assert_eq!(
+    ///     email.with_local_part("simon johnston").unwrap().as_str(),
+    ///     "Simon <\"simon johnston\"@example.com>"
+    /// );
+    /// ```
+    ///
+    pub fn with_local_part(&self, local_part: &str) -> Result<Self, Error> {
+        build_address(
+            self.display_part(),
+            &local_part_from_value(local_part),
+            self.domain(),
+        )
+    }
+
+    ///
+    /// Returns a copy of this address with the `domain` replaced by `domain`, either a domain
+    /// name or a domain literal. The new domain is validated with the default [Options], so
+    /// comments and general address literals are not allowed, and an error is returned if the
+    /// result is not a valid address.
+    ///
+    /// ```rust
+    /// use email_address::*;
+    /// use std::str::FromStr;
+    ///
+    /// let email = EmailAddress::from_str("Simon <simon@example.com>").unwrap();
+    ///
+    /// assert_eq!(
+    ///     email.with_domain("example.org").unwrap().as_str(),
+    ///     "Simon <simon@example.org>"
+    /// );
+    /// assert_eq!(email.with_domain("example..org"), Err(Error::SubDomainEmpty));
+    /// ```
+    ///
+    pub fn with_domain(&self, domain: &str) -> Result<Self, Error> {
+        parse_domain(domain, Options::default())?;
+        build_address(self.display_part(), self.local_part(), domain)
+    }
+
+    ///
+    /// Returns a copy of this address with the display name replaced by `display_name`, which
+    /// is written as by `to_display`. If `display_name` is empty the display name is removed.
+    ///
+    /// ```rust
+    /// use email_address::*;
+    /// use std::str::FromStr;
+    ///
+    /// let email = EmailAddress::from_str("Simon <simon@example.com>").unwrap();
+    ///
+    /// assert_eq!(
+    ///     email.with_display_name("Johnston, Simon").as_str(),
+    ///     "\"Johnston, Simon\" <simon@example.com>"
+    /// );
+    /// assert_eq!(email.with_display_name("").as_str(), "simon@example.com");
+    /// ```
+    ///
+    pub fn with_display_name(&self, display_name: &str) -> Self {
+        Self::new_unchecked(self.to_display(display_name))
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Functions
+// ------------------------------------------------------------------------------------------------
+
+///
+/// Returns the value of a `local-part`, without the quotes around a `quoted-string` and with
+/// each `quoted-pair` replaced by the character it quotes.
+///
+fn unquote_local_part(part: &str) -> Cow<'_, str> {
+    if part.len() < 2 || !part.starts_with(DQUOTE) || !part.ends_with(DQUOTE) {
+        return Cow::Borrowed(part);
+    }
+    let content = &part[1..part.len() - 1];
+    if !content.contains(ESC) {
+        return Cow::Borrowed(content);
+    }
+    let mut result = String::with_capacity(content.len());
+    let mut char_iter = content.chars();
+    while let Some(c) = char_iter.next() {
+        if c == ESC {
+            if let Some(c) = char_iter.next() {
+                result.push(c);
+            }
+        } else {
+            result.push(c);
+        }
+    }
+    Cow::Owned(result)
+}
+
+///
+/// Write `value` as a `quoted-string`, with only the `"` and `\` characters as quoted-pairs.
+///
+fn quote_local_part(value: &str) -> String {
+    let mut result = String::with_capacity(value.len() + 2);
+    result.push(DQUOTE);
+    for c in value.chars() {
+        if c == DQUOTE || c == ESC {
+            result.push(ESC);
+        }
+        result.push(c);
+    }
+    result.push(DQUOTE);
+    result
+}
+
+///
+/// Returns the `local-part` for `value`, which is quoted only if it is not a valid `dot-atom`.
+///
+fn local_part_from_value(value: &str) -> Cow<'_, str> {
+    if is_dot_atom_text(value) {
+        Cow::Borrowed(value)
+    } else {
+        Cow::Owned(quote_local_part(value))
+    }
+}
+
+///
+/// Build a new address from its components, which is parsed to ensure that it is valid. Any
+/// kind of domain literal, and comments, are allowed as these may be present in components
+/// taken from an existing address; any component supplied by a caller must be validated with
+/// the appropriate options first.
+///
+fn build_address(display: &str, local_part: &str, domain: &str) -> Result<EmailAddress, Error> {
+    let address = if display.is_empty() {
+        format!("{}{}{}", local_part, AT, domain)
+    } else {
+        format!(
+            "{} {}{}{}{}{}",
+            display, DISPLAY_START, local_part, AT, domain, DISPLAY_END
+        )
+    };
+    parse_address(
+        &address,
+        Options::default()
+            .with_display_text()
+            .with_domain_literal()
+            .with_general_literal()
+            .with_comments(),
+    )
+}
+
+///
+/// Returns the `domain` case-folded, domain names are case-insensitive and UTS #46 maps all
+/// characters in a U-label to lowercase.
+///
+fn fold_domain(domain: &str) -> Cow<'_, str> {
+    if domain.chars().any(char::is_uppercase) {
+        Cow::Owned(domain.chars().flat_map(char::to_lowercase).collect())
+    } else {
+        Cow::Borrowed(domain)
+    }
+}
+
+///
+/// Compare two `local-part`s by their values, as returned by `unquote_local_part` once any CFWS
+/// is removed. Where neither has quotes or CFWS the text is compared directly, otherwise the
+/// characters of each value are compared as they are found, so that nothing is copied.
+///
+fn cmp_local_part_values(part: &str, other: &str) -> Ordering {
+    let is_value =
+        |part: &str| !part.contains(|c: char| c == DQUOTE || c == ESC || is_cfws_start(c));
+    if is_value(part) && is_value(other) {
+        part.cmp(other)
+    } else {
+        local_part_value_chars(part).cmp(local_part_value_chars(other))
+    }
+}
+
+///
+/// Compare two `domain`s case-insensitively, as `fold_domain` once any CFWS is removed, and
+/// without copying either.
+///
+fn cmp_domain_values(domain: &str, other: &str) -> Ordering {
+    let is_folded = |part: &str| !part.contains(|c: char| c.is_uppercase() || is_cfws_start(c));
+    if is_folded(domain) && is_folded(other) {
+        domain.cmp(other)
+    } else {
+        folded_domain_chars(domain).cmp(folded_domain_chars(other))
+    }
+}
+
+///
+/// Hash the value of a component given as its characters, so that the same value is hashed
+/// identically however it is written.
+///
+fn hash_chars<H: Hasher>(chars: impl Iterator<Item = char>, state: &mut H) {
+    for c in chars {
+        state.write_u32(c as u32);
+    }
+    // Not a `char`, this separates the values of successive components.
+    state.write_u32(u32::MAX);
+}
+
+///
+/// Returns the characters of the value of a `local-part`, as `cmp_local_part_values`.
+///
+fn local_part_value_chars(part: &str) -> impl Iterator<Item = char> + '_ {
+    let mut char_iter = uncommented_chars(part);
+    std::iter::from_fn(move || loop {
+        match char_iter.next()? {
+            DQUOTE => (),
+            ESC => return char_iter.next(),
+            c => return Some(c),
+        }
+    })
+}
+
+///
+/// Returns the characters of a `domain` case-folded, as `cmp_domain_values`.
+///
+fn folded_domain_chars(domain: &str) -> impl Iterator<Item = char> + '_ {
+    uncommented_chars(domain).flat_map(char::to_lowercase)
+}
+
+///
+/// Returns the characters of a valid `local-part` or `domain` with any CFWS removed, as
+/// `strip_cfws`, but without copying the text.
+///
+fn uncommented_chars(part: &str) -> impl Iterator<Item = char> + '_ {
+    let mut char_iter = part.chars().peekable();
+    // The closing delimiter while within a quoted string or domain literal.
+    let mut end = None;
+    let mut escaped = false;
+    std::iter::from_fn(move || loop {
+        let c = char_iter.next()?;
+        if let Some(delimiter) = end {
+            if escaped {
+                escaped = false;
+            } else if c == ESC {
+                escaped = true;
+            } else if c == delimiter {
+                end = None;
+            }
+            return Some(c);
+        }
+        match c {
+            LPAREN => skip_comment(&mut char_iter).ok()?,
+            DQUOTE => {
+                end = Some(DQUOTE);
+                return Some(c);
+            }
+            LBRACKET => {
+                end = Some(RBRACKET);
+                return Some(c);
+            }
+            c if is_cfws_start(c) => (),
+            c => return Some(c),
+        }
+    })
+}
+
+///
+/// Returns the labels of a `domain` starting with the top-level domain; a `domain-literal` is
+/// returned as a single label.
+///
+fn reversed_labels(domain: &str) -> impl Iterator<Item = &str> {
+    let literal = domain.starts_with(LBRACKET);
+    domain.rsplit(move |c| !literal && c == DOT)
+}
+
+///
+/// Parse a `domain` into a [Host]; any comments around a domain literal are ignored, and a
+/// domain name is returned as-is.
+///
+fn parse_host(domain: &str) -> Host<'_> {
+    match (domain.find(LBRACKET), domain.rfind(RBRACKET)) {
+        (Some(start), Some(end)) if start < end => {
+            let literal = &domain[start + 1..end];
+            if let Some(address) = strip_ipv6_tag(literal).and_then(parse_ipv6_literal) {
+                Host::Ipv6(address)
+            } else if let Some(address) = parse_ipv4_literal(literal) {
+                Host::Ipv4(address)
+            } else if let Some((tag, content)) = parse_general_literal(literal) {
+                Host::General { tag, content }
+            } else {
+                Host::Domain(domain)
+            }
+        }
+        _ => Host::Domain(domain),
+    }
+}
+
+fn parse_address(address: &str, options: Options) -> Result<EmailAddress, Error> {
+    parse_address_ref(address, options).map(|email| email.to_owned())
+}
+
+fn parse_address_detailed(address: &str, options: Options) -> Result<EmailAddress, ParseError> {
+    parse_address_ref_detailed(address, options).map(|email| email.to_owned())
+}
+
+fn parse_address_ref(address: &str, options: Options) -> Result<EmailAddressRef<'_>, Error> {
+    let (display, local_part, domain) = parse_address_with(address, options, &mut fail_fast)?;
+    let parts = Parts::from_components(address, display, local_part, domain);
+    Ok(EmailAddressRef::from_parts(address, parts))
+}
+
+///
+/// Parse `address` as `parse_address_ref`, locating any error within `address`; the work of
+/// locating the error is only done when parsing fails.
+///
+fn parse_address_ref_detailed(
+    address: &str,
+    options: Options,
+) -> Result<EmailAddressRef<'_>, ParseError> {
+    let mut error = None;
+    let result = parse_address_with(address, options, &mut |kind, component, subject| {
+        error = Some(locate(address, subject, component, options, kind.clone()));
+        Err(kind)
+    });
+    match (result, error) {
+        (Ok((display, local_part, domain)), _) => Ok(EmailAddressRef::from_parts(
+            address,
+            Parts::from_components(address, display, local_part, domain),
+        )),
+        (Err(_), Some(error)) => Err(error),
+        (Err(kind), None) => Err(ParseError::new(
+            kind,
+            0..address.len(),
+            None,
+            Component::Address,
+        )),
+    }
+}
+
+///
+/// Parse `address`, passing each error found to `report`, and returning the display part,
+/// `local-part`, and `domain`. Parsing stops at the first error that `report` returns, and
+/// when the address cannot be split into its components.
+///
+fn parse_address_with<'a>(
+    address: &'a str,
+    options: Options,
+    report: &mut Report<'_>,
+) -> Result<(&'a str, &'a str, &'a str), Error> {
+    //
+    // Deals with cases of '@' in `local-part`, if it is quoted they are legal, if
+    // not then they'll return an `InvalidCharacter` error later.
+    //
+    let (display, email) = match split_display_email(address) {
+        Ok(parts) => parts,
+        Err(kind) => {
+            report(kind.clone(), Component::Address, &address[address.len()..])?;
+            return Err(kind);
+        }
+    };
+    let (local_part, domain) = match split_at(email) {
+        Ok(parts) => parts,
+        Err(kind) => {
+            report(kind.clone(), Component::Address, email)?;
+            return Err(kind);
+        }
+    };
+
+    if !display.is_empty() && !options.allow_display_text {
+        report(
+            Error::UnsupportedDisplayName,
+            Component::DisplayName,
+            display,
+        )?;
+    } else if display.is_empty() && local_part.starts_with(DISPLAY_START) {
+        let (kind, component) = if options.allow_display_text {
+            (Error::MissingDisplayName, Component::DisplayName)
+        } else {
+            (Error::InvalidCharacter, Component::LocalPart)
+        };
+        report(kind.clone(), component, &local_part[..1])?;
+        return Err(kind);
+    }
+
+    parse_local_part_with(local_part, options, report)?;
+    parse_domain_with(domain, options, report)?;
+    Ok((display, local_part, domain))
+}
+
+fn split_parts(address: &str) -> Result<(&str, &str, &str), Error> {
+    let (display, email) = split_display_email(address)?;
+    let (local_part, domain) = split_at(email)?;
+    Ok((local_part, domain, display))
+}
+
+fn split_display_email(text: &str) -> Result<(&str, &str), Error> {
+    let (display_start, display_end, email_start, email_end) =
+        grammar::split_display_email(text.as_bytes(), 0, text.len())?;
+    Ok((
+        &text[display_start..display_end],
+        &text[email_start..email_end],
+    ))
+}
+
+fn split_at(address: &str) -> Result<(&str, &str), Error> {
+    match grammar::find_separator(address.as_bytes(), 0, address.len()) {
+        None => Error::MissingSeparator.into(),
+        Some(index) => Ok((&address[..index], &address[index + 1..])),
+    }
+}
+
+fn parse_local_part(part: &str, options: Options) -> Result<(), Error> {
+    parse_local_part_with(part, options, &mut fail_fast)
+}
+
+fn parse_local_part_with(
+    part: &str,
+    options: Options,
+    report: &mut Report<'_>,
+) -> Result<(), Error> {
+    parse_uncommented(
+        part,
+        options,
+        Component::LocalPart,
+        report,
+        parse_local_part_text,
+    )
+}
+
+fn parse_local_part_text(
+    part: &str,
+    options: Options,
+    report: &mut Report<'_>,
+) -> Result<(), Error> {
+    let mut report = |kind| report(kind, Component::LocalPart, part);
+    if part.is_empty() {
+        return report(Error::LocalPartEmpty);
+    }
+    if part.len() > LOCAL_PART_MAX_LENGTH {
+        report(Error::LocalPartTooLong)?;
+    }
+    if let Err(kind) = parse_local_part_content(part) {
+        report(kind)
+    } else if !options.allow_smtputf8 && !part.is_ascii() {
+        report(Error::RequiresSmtpUtf8)
+    } else {
+        Ok(())
+    }
+}
+
+fn parse_local_part_content(part: &str) -> Result<(), Error> {
+    grammar::local_part(part.as_bytes(), 0, part.len())
+}
+
+fn parse_domain(part: &str, options: Options) -> Result<(), Error> {
+    parse_domain_with(part, options, &mut fail_fast)
+}
+
+fn parse_domain_with(part: &str, options: Options, report: &mut Report<'_>) -> Result<(), Error> {
+    parse_uncommented(part, options, Component::Domain, report, parse_domain_value)
+}
+
+///
+/// Parse a `domain` with any CFWS removed; if SMTPUTF8 is not allowed a Unicode domain must
+/// also be convertible to A-labels, which is only checked if no other error is found.
+///
+fn parse_domain_value(part: &str, options: Options, report: &mut Report<'_>) -> Result<(), Error> {
+    let mut valid = true;
+    parse_domain_text_with(part, options, &mut |kind, component, subject| {
+        valid = false;
+        report(kind, component, subject)
+    })?;
+    if !valid || options.allow_smtputf8 || part.is_ascii() {
+        Ok(())
+    } else if part.starts_with(LBRACKET) {
+        report(Error::RequiresSmtpUtf8, Component::Domain, part)
+    } else {
+        // A Unicode domain can still be used without SMTPUTF8 as A-labels.
+        domain_to_ascii(part)
+            .map(|_| ())
+            .or_else(|kind| report(kind, Component::Domain, part))
+    }
+}
+
+fn parse_domain_text(part: &str, options: Options) -> Result<(), Error> {
+    parse_domain_text_with(part, options, &mut fail_fast)
+}
+
+fn parse_domain_text_with(
+    part: &str,
+    options: Options,
+    report: &mut Report<'_>,
+) -> Result<(), Error> {
+    if part.is_empty() {
+        return report(Error::DomainEmpty, Component::Domain, part);
+    }
+    if part.len() > DOMAIN_MAX_LENGTH {
+        report(Error::DomainTooLong, Component::Domain, part)?;
+    }
+    if part.starts_with(LBRACKET) && part.ends_with(RBRACKET) {
+        if options.allow_domain_literal {
+            parse_literal_domain(&part[1..part.len() - 1], options)
+        } else {
+            Error::UnsupportedDomainLiteral.into()
+        }
+        .or_else(|kind| report(kind, Component::Domain, part))
+    } else {
+        parse_text_domain(part, options, report)
+    }
+}
+
+fn parse_text_domain(part: &str, options: Options, report: &mut Report<'_>) -> Result<(), Error> {
+    let mut sub_domains = 0;
+
+    for (index, sub_part) in part.split(DOT).enumerate() {
+        parse_sub_domain(sub_part)
+            .or_else(|kind| report(kind, Component::DomainLabel(index), sub_part))?;
+        sub_domains += 1;
+    }
+
+    if sub_domains < options.minimum_sub_domains {
+        report(Error::DomainTooFew, Component::Domain, part)
+    } else {
+        Ok(())
+    }
+}
+
+fn parse_sub_domain(sub_part: &str) -> Result<(), Error> {
+    // The first and last characters must be letters or digits, which for a non-ASCII character
+    // depends on the Unicode tables.
+    if sub_part.starts_with(|c: char| !c.is_alphanumeric())
+        || sub_part.ends_with(|c: char| !c.is_alphanumeric())
+    {
+        return Error::InvalidCharacter.into();
+    }
+
+    grammar::sub_domain(sub_part.as_bytes(), 0, sub_part.len())?;
+
+    if is_ace_label(sub_part) {
+        let _ = decode_a_label(sub_part)?;
+    }
+
+    Ok(())
+}
+
+fn parse_literal_domain(part: &str, options: Options) -> Result<(), Error> {
+    if !part.chars().all(is_dtext_char) {
+        return Error::InvalidCharacter.into();
+    }
+    if let Some(address) = strip_ipv6_tag(part) {
+        if !options.allow_ipv6_literal {
+            Error::UnsupportedDomainLiteral.into()
+        } else if parse_ipv6_literal(address).is_none() {
+            Error::InvalidIPAddress.into()
+        } else {
+            Ok(())
+        }
+    } else if part.chars().all(|c| c.is_ascii_digit() || c == DOT) {
+        if !options.allow_ipv4_literal {
+            Error::UnsupportedDomainLiteral.into()
+        } else if parse_ipv4_literal(part).is_none() {
+            Error::InvalidIPAddress.into()
+        } else {
+            Ok(())
+        }
+    } else if options.allow_general_literal && parse_general_literal(part).is_some() {
+        Ok(())
+    } else {
+        Error::InvalidIPAddress.into()
+    }
 }
 
-// ------------------------------------------------------------------------------------------------
+fn strip_ipv6_tag(part: &str) -> Option<&str> {
+    if grammar::has_ipv6_tag(part.as_bytes(), 0, part.len()) {
+        Some(&part[IPV6_TAG.len()..])
+    } else {
+        None
+    }
+}
+
+fn parse_ipv4_literal(part: &str) -> Option<Ipv4Addr> {
+    grammar::ipv4_address(part.as_bytes(), 0, part.len()).map(Ipv4Addr::from)
+}
+
+fn parse_ipv6_literal(part: &str) -> Option<Ipv6Addr> {
+    grammar::ipv6_address(part.as_bytes(), 0, part.len()).map(Ipv6Addr::from)
+}
+
+///
+/// Parse the RFC 5321 `General-address-literal` production, returning the tag and content.
+///
+fn parse_general_literal(part: &str) -> Option<(&str, &str)> {
+    let (tag, content) = part.split_once(IPV6_SEP)?;
+    if is_ldh_str(tag) && !content.is_empty() && content.chars().all(is_dtext_char) {
+        Some((tag, content))
+    } else {
+        None
+    }
+}
+
+///
+/// Remove any CFWS from `part`, if comments are allowed, and `parse` the remaining text. Errors
+/// in the remaining text are reported against the whole of `part`, as positions within it no
+/// longer correspond to positions in `part`.
+///
+/// The remaining text is copied to the stack, so that nothing is allocated; only text which is
+/// too long for the component is copied to the heap, and then only if `report` continues past
+/// the error.
+///
+fn parse_uncommented(
+    part: &str,
+    options: Options,
+    component: Component,
+    report: &mut Report<'_>,
+    parse: fn(&str, Options, &mut Report<'_>) -> Result<(), Error>,
+) -> Result<(), Error> {
+    if !options.allow_comments || !part.contains(is_cfws_start) {
+        return parse(part, options, report);
+    }
+    let (max_length, too_long) = match component {
+        Component::LocalPart => (LOCAL_PART_MAX_LENGTH, Error::LocalPartTooLong),
+        _ => (DOMAIN_MAX_LENGTH, Error::DomainTooLong),
+    };
+    let mut report = |kind, _, _: &str| report(kind, component, part);
+    let mut buffer = [0_u8; DOMAIN_MAX_LENGTH];
+    match strip_cfws_into(part, &mut buffer[..max_length]) {
+        Ok(Some(stripped)) => parse(stripped, options, &mut report),
+        Ok(None) => {
+            report(too_long.clone(), component, part)?;
+            let stripped = strip_cfws(part)?;
+            parse(&stripped, options, &mut |kind, component, subject| {
+                if kind == too_long {
+                    Ok(())
+                } else {
+                    report(kind, component, subject)
+                }
+            })
+        }
+        Err(kind) => report(kind, component, part),
+    }
+}
+
+///
+/// The [Report] used to stop parsing at the first error.
+///
+fn fail_fast(kind: Error, _: Component, _: &str) -> Result<(), Error> {
+    Err(kind)
+}
+
+///
+/// Remove any comments and folding white space from either a `local-part` or `domain`,
+/// returning the remaining text. `CFWS` may only appear at the start or end of the part, or
+/// either side of a `.` separator; quoted strings and domain literals are copied unchanged.
+///
+fn strip_cfws(part: &str) -> Result<Cow<'_, str>, Error> {
+    if !part.contains(is_cfws_start) {
+        return Ok(Cow::Borrowed(part));
+    }
+
+    let mut result = String::with_capacity(part.len());
+    scan_cfws(part, |c| result.push(c))?;
+    Ok(Cow::Owned(result))
+}
+
+///
+/// Remove any comments and folding white space from `part`, as `strip_cfws`, copying the
+/// remaining text into `buffer` rather than allocating. Returns `None` if the remaining text
+/// does not fit within `buffer`.
+///
+fn strip_cfws_into<'a>(part: &str, buffer: &'a mut [u8]) -> Result<Option<&'a str>, Error> {
+    let mut length = 0;
+    let mut fits = true;
+    scan_cfws(part, |c| {
+        fits = fits && length + c.len_utf8() <= buffer.len();
+        if fits {
+            length += c.encode_utf8(&mut buffer[length..]).len();
+        }
+    })?;
+    let buffer: &'a [u8] = buffer;
+    Ok(if fits {
+        std::str::from_utf8(&buffer[..length]).ok()
+    } else {
+        None
+    })
+}
+
+///
+/// Scan `part` for comments and folding white space, passing each remaining character to
+/// `keep`, and returning any error in the placement or content of the `CFWS`.
+///
+fn scan_cfws(part: &str, mut keep: impl FnMut(char)) -> Result<(), Error> {
+    // The last character kept, `CFWS` must follow either the start of the part or a `.`.
+    let mut last = None;
+    // Set when CFWS has been skipped, the value is true if the CFWS included a comment.
+    let mut skipped: Option<bool> = None;
+    let mut char_iter = part.chars().peekable();
+    while let Some(c) = char_iter.next() {
+        if c == LPAREN {
+            skip_comment(&mut char_iter)?;
+            skipped = Some(true);
+        } else if is_wsp(c) {
+            let _ = skipped.get_or_insert(false);
+        } else if c == CR {
+            skip_folding(&mut char_iter).ok_or(Error::InvalidCharacter)?;
+            let _ = skipped.get_or_insert(false);
+        } else {
+            if let Some(had_comment) = skipped.take() {
+                if !(matches!(last, None | Some(DOT)) || c == DOT) {
+                    return Err(if had_comment {
+                        Error::InvalidComment
+                    } else {
+                        Error::InvalidCharacter
+                    });
+                }
+            }
+            keep(c);
+            last = Some(c);
+            let end = match c {
+                DQUOTE => DQUOTE,
+                LBRACKET => RBRACKET,
+                _ => continue,
+            };
+            while let Some(c) = char_iter.next() {
+                keep(c);
+                last = Some(c);
+                if c == ESC {
+                    if let Some(c) = char_iter.next() {
+                        keep(c);
+                        last = Some(c);
+                    }
+                } else if c == end {
+                    break;
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+///
+/// Skip over a, possibly nested, comment; the opening `(` has already been consumed.
+///
+fn skip_comment<I>(char_iter: &mut Peekable<I>) -> Result<(), Error>
+where
+    I: Iterator<Item = char>,
+{
+    let mut depth = 1;
+    while let Some(c) = char_iter.next() {
+        match c {
+            LPAREN => depth += 1,
+            RPAREN => {
+                depth -= 1;
+                if depth == 0 {
+                    return Ok(());
+                }
+            }
+            ESC => match char_iter.next() {
+                // quoted-pair
+                Some(c) if is_vchar(c) || is_wsp(c) || !c.is_ascii() => (),
+                _ => return Error::InvalidComment.into(),
+            },
+            CR => {
+                skip_folding(char_iter).ok_or(Error::InvalidComment)?;
+            }
+            c if is_wsp(c) || is_ctext_char(c) => (),
+            _ => return Error::InvalidComment.into(),
+        }
+    }
+    Error::InvalidComment.into()
+}
 
-fn is_atext(c: char) -> bool {
-    c.is_alphanumeric()
-        || c == '!'
-        || c == '#'
-        || c == '$'
-        || c == '%'
-        || c == '&'
-        || c == '\''
-        || c == '*'
-        || c == '+'
-        || c == '-'
-        || c == '/'
-        || c == '='
-        || c == '?'
-        || c == '^'
-        || c == '_'
-        || c == '`'
-        || c == '{'
-        || c == '|'
-        || c == '}'
-        || c == '~'
-        || !c.is_ascii()
+///
+/// Skip the remainder of a folding line break, the `CR` has already been consumed and must
+/// be followed by `LF` and at least one `WSP` character.
+///
+fn skip_folding<I>(char_iter: &mut Peekable<I>) -> Option<()>
+where
+    I: Iterator<Item = char>,
+{
+    match (char_iter.next(), char_iter.peek()) {
+        (Some(LF), Some(c)) if is_wsp(*c) => Some(()),
+        _ => None,
+    }
 }
 
+// ------------------------------------------------------------------------------------------------
+
 //fn is_special(c: char) -> bool {
 //    c == '('
 //        || c == ')'
@@ -1110,57 +2686,22 @@ fn is_atext(c: char) -> bool {
 //}
 
 fn is_atom(s: &str) -> bool {
-    !s.is_empty() && s.chars().all(is_atext)
+    grammar::is_atom(s.as_bytes(), 0, s.len())
 }
 
 fn is_dot_atom_text(s: &str) -> bool {
-    s.split(DOT).all(is_atom)
+    grammar::is_dot_atom_text(s.as_bytes(), 0, s.len())
 }
 
-fn is_vchar(c: char) -> bool {
-    ('\x21'..='\x7E').contains(&c)
+fn is_ldh_str(s: &str) -> bool {
+    s.ends_with(|c: char| c.is_ascii_alphanumeric())
+        && s.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
 }
 
-fn is_wsp(c: char) -> bool {
-    c == SP || c == HTAB
+fn is_cfws_start(c: char) -> bool {
+    c == LPAREN || c == CR || c == LF || is_wsp(c)
 }
 
-fn is_qtext_char(c: char) -> bool {
-    c == '\x21' || ('\x23'..='\x5B').contains(&c) || ('\x5D'..='\x7E').contains(&c) || !c.is_ascii()
-}
-
-fn is_qcontent(s: &str) -> bool {
-    let mut char_iter = s.chars();
-    while let Some(c) = &char_iter.next() {
-        if c == &ESC {
-            // quoted-pair
-            match char_iter.next() {
-                Some(c2) if is_vchar(c2) => (),
-                _ => return false,
-            }
-        } else if !(is_wsp(*c) || is_qtext_char(*c)) {
-            // qtext
-            return false;
-        }
-    }
-    true
-}
-
-fn is_dtext_char(c: char) -> bool {
-    ('\x21'..='\x5A').contains(&c) || ('\x5E'..='\x7E').contains(&c) || !c.is_ascii()
-}
-
-//fn is_ctext_char(c: char) -> bool {
-//    (c >= '\x21' && c == '\x27')
-//        || ('\x2A'..='\x5B').contains(&c)
-//        || ('\x5D'..='\x7E').contains(&c)
-//        || !c.is_ascii()
-//}
-//
-//fn is_ctext(s: &str) -> bool {
-//    s.chars().all(is_ctext_char)
-//}
-
 // ------------------------------------------------------------------------------------------------
 // Unit Tests
 // ------------------------------------------------------------------------------------------------
@@ -1419,84 +2960,93 @@ mod tests {
     #[test]
     fn test_good_examples_02() {
         valid_with_options(
-            "email@[127.0.0.256]",
+            "email@[127.0.0.255]",
             Options {
                 allow_domain_literal: true,
                 ..Default::default()
             },
-            Some("minimum sub domains"),
+            Some("IPv4 literal"),
         );
     }
 
     #[test]
     fn test_good_examples_03() {
         valid_with_options(
-            "email@[2001:db8::12345]",
+            "email@[IPv6:2001:db8::1234]",
             Options {
                 allow_domain_literal: true,
                 ..Default::default()
             },
-            Some("minimum sub domains"),
+            Some("compressed IPv6 literal"),
         );
     }
 
     #[test]
     fn test_good_examples_04() {
         valid_with_options(
-            "email@[2001:db8:0:0:0:0:1]",
+            "email@[IPv6:2001:db8:0:0:0:0:0:1]",
             Options {
                 allow_domain_literal: true,
                 ..Default::default()
             },
-            Some("minimum sub domains"),
+            Some("full IPv6 literal"),
         );
     }
 
     #[test]
     fn test_good_examples_05() {
         valid_with_options(
-            "email@[::ffff:127.0.0.256]",
+            "email@[IPv6:::ffff:127.0.0.255]",
             Options {
                 allow_domain_literal: true,
                 ..Default::default()
             },
-            Some("minimum sub domains"),
+            Some("compressed IPv4-mapped IPv6 literal"),
         );
     }
 
     #[test]
     fn test_good_examples_06() {
         valid_with_options(
-            "email@[2001:dg8::1]",
+            "email@[IPv6:0:0:0:0:0:ffff:127.0.0.1]",
             Options {
                 allow_domain_literal: true,
                 ..Default::default()
             },
-            Some("minimum sub domains"),
+            Some("full IPv4-mapped IPv6 literal"),
         );
     }
 
     #[test]
     fn test_good_examples_07() {
         valid_with_options(
-            "email@[2001:dG8:0:0:0:0:0:1]",
+            "email@[ipv6:2001:DB8::]",
             Options {
                 allow_domain_literal: true,
                 ..Default::default()
             },
-            Some("minimum sub domains"),
+            Some("lower case tag, trailing compression"),
         );
     }
 
     #[test]
     fn test_good_examples_08() {
         valid_with_options(
-            "email@[::fTzF:127.0.0.1]",
+            "email@[IPv6:::]",
             Options {
                 allow_domain_literal: true,
                 ..Default::default()
             },
-            Some("minimum sub domains"),
+            Some("unspecified IPv6 address"),
+        );
+    }
+
+    #[test]
+    fn test_good_examples_09() {
+        valid_with_options(
+            "email@[x-tag:some-content]",
+            Options::default().with_general_literal(),
+            Some("general address literal"),
         );
     }
 
@@ -1523,11 +3073,69 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_to_display_quoted() {
+        let email = EmailAddress::from_str("Jack <john@example.com>").unwrap();
+
+        assert_eq!(
+            email.to_display("Doe, John"),
+            String::from("\"Doe, John\" <john@example.com>")
+        );
+
+        let display = email.to_display("John \"Jack\" <Doe>");
+        let parsed = EmailAddress::from_str(&display).unwrap();
+        assert_eq!(parsed.display_name(), "John \"Jack\" <Doe>");
+        assert_eq!(parsed.email(), "john@example.com");
+    }
+
+    #[test]
+    fn test_to_encoded_display() {
+        let email = EmailAddress::from_str("коля@пример.рф").unwrap();
+
+        let display = email.to_encoded_display("коля");
+        assert_eq!(
+            display,
+            String::from("=?UTF-8?B?0LrQvtC70Y8=?= <коля@пример.рф>")
+        );
+        assert_eq!(
+            EmailAddress::from_str(&display).unwrap().display_name(),
+            "коля"
+        );
+    }
+
     #[test]
     fn test_touri() {
         let email = EmailAddress::from_str("коля@пример.рф").unwrap();
 
-        assert_eq!(email.to_uri(), String::from("mailto:коля@пример.рф"));
+        assert_eq!(
+            email.to_uri(),
+            String::from(
+                "mailto:%D0%BA%D0%BE%D0%BB%D1%8F@%D0%BF%D1%80%D0%B8%D0%BC%D0%B5%D1%80.%D1%80%D1%84"
+            )
+        );
+    }
+
+    #[test]
+    fn test_from_uri_roundtrip() {
+        for address in [
+            "коля@пример.рф",
+            "\"a,b? c\"@[127.0.0.1]",
+            "!#$%&'*+-/=?^_`.{|}~@example.com",
+            "\"Joe.\\\\Blow\"@example.com",
+        ] {
+            let email = EmailAddress::from_str(address).unwrap();
+            assert_eq!(EmailAddress::from_uri(&email.to_uri()).unwrap(), email);
+        }
+    }
+
+    #[test]
+    fn test_touri_reserved() {
+        let email = EmailAddress::from_str("Simon <\"a,b? c\"@[127.0.0.1]>").unwrap();
+
+        assert_eq!(
+            email.to_uri(),
+            String::from("mailto:%22a%2Cb%3F%20c%22@%5B127.0.0.1%5D")
+        );
     }
 
     // ------------------------------------------------------------------------------------------------
@@ -1788,22 +3396,284 @@ mod tests {
     }
 
     #[test]
-    fn test_bad_example_19() {
-        expect_with_options(
-            "email@[2001:db8:0:0:0:0:1]",
-            Options::default().without_domain_literal(),
-            Error::UnsupportedDomainLiteral,
-            Some("unsupported domain literal (3)"),
+    fn test_bad_example_19() {
+        expect_with_options(
+            "email@[2001:db8:0:0:0:0:1]",
+            Options::default().without_domain_literal(),
+            Error::UnsupportedDomainLiteral,
+            Some("unsupported domain literal (3)"),
+        );
+    }
+
+    #[test]
+    fn test_bad_example_20() {
+        expect_with_options(
+            "email@[::ffff:127.0.0.256]",
+            Options::default().without_domain_literal(),
+            Error::UnsupportedDomainLiteral,
+            Some("unsupported domain literal (4)"),
+        );
+    }
+
+    #[test]
+    fn test_bad_example_21() {
+        expect(
+            "email@[127.0.0.256]",
+            Error::InvalidIPAddress,
+            Some("IPv4 octet out of range"),
+        );
+    }
+
+    #[test]
+    fn test_bad_example_22() {
+        expect(
+            "email@[2001:db8::12345]",
+            Error::InvalidIPAddress,
+            Some("IPv6 without tag"),
+        );
+    }
+
+    #[test]
+    fn test_bad_example_23() {
+        expect(
+            "email@[IPv6:2001:db8::12345]",
+            Error::InvalidIPAddress,
+            Some("IPv6 group too long"),
+        );
+    }
+
+    #[test]
+    fn test_bad_example_24() {
+        expect(
+            "email@[IPv6:2001:db8:0:0:0:0:1]",
+            Error::InvalidIPAddress,
+            Some("IPv6 too few groups"),
+        );
+    }
+
+    #[test]
+    fn test_bad_example_25() {
+        expect(
+            "email@[IPv6:::ffff:127.0.0.256]",
+            Error::InvalidIPAddress,
+            Some("IPv4-mapped octet out of range"),
+        );
+    }
+
+    #[test]
+    fn test_bad_example_26() {
+        expect(
+            "email@[IPv6:2001:dg8::1]",
+            Error::InvalidIPAddress,
+            Some("IPv6 invalid hex digit"),
+        );
+    }
+
+    #[test]
+    fn test_bad_example_27() {
+        expect(
+            "email@[IPv6:1:2:3:4:5:6:7::]",
+            Error::InvalidIPAddress,
+            Some("IPv6 compression of a single group"),
+        );
+    }
+
+    #[test]
+    fn test_bad_example_28() {
+        expect(
+            "email@[IPv6:1::2::3]",
+            Error::InvalidIPAddress,
+            Some("IPv6 multiple compressions"),
+        );
+    }
+
+    #[test]
+    fn test_bad_example_29() {
+        expect(
+            "email@[IPv6:1.2.3.4::]",
+            Error::InvalidIPAddress,
+            Some("IPv4 must be last"),
+        );
+    }
+
+    #[test]
+    fn test_bad_example_30() {
+        expect(
+            "email@[127.0.0]",
+            Error::InvalidIPAddress,
+            Some("IPv4 too few octets"),
+        );
+    }
+
+    #[test]
+    fn test_bad_example_31() {
+        expect(
+            "email@[x-tag:some-content]",
+            Error::InvalidIPAddress,
+            Some("general literal not enabled"),
+        );
+    }
+
+    #[test]
+    fn test_bad_example_32() {
+        expect_with_options(
+            "email@[127.0.0.1]",
+            Options::default().without_ipv4_literal(),
+            Error::UnsupportedDomainLiteral,
+            Some("unsupported IPv4 literal"),
+        );
+        expect_with_options(
+            "email@[IPv6:2001:db8::1]",
+            Options::default().without_ipv6_literal(),
+            Error::UnsupportedDomainLiteral,
+            Some("unsupported IPv6 literal"),
+        );
+    }
+
+    #[test]
+    fn test_host_domain() {
+        let email = EmailAddress::from_str("simon@example.com").unwrap();
+
+        assert_eq!(email.host(), Host::Domain("example.com"));
+        assert!(email.host().is_domain());
+        assert_eq!(email.host().ip_addr(), None);
+    }
+
+    #[test]
+    fn test_host_ipv4() {
+        let email = EmailAddress::from_str("simon@[192.168.2.1]").unwrap();
+
+        assert_eq!(email.host(), Host::Ipv4(Ipv4Addr::new(192, 168, 2, 1)));
+        assert_eq!(email.host().to_string(), "[192.168.2.1]");
+    }
+
+    #[test]
+    fn test_host_ipv6() {
+        let email = EmailAddress::from_str("simon@[IPv6:::ffff:192.168.2.1]").unwrap();
+
+        assert_eq!(
+            email.host().ip_addr(),
+            Some(IpAddr::V6(Ipv4Addr::new(192, 168, 2, 1).to_ipv6_mapped()))
+        );
+        assert_eq!(email.host().to_string(), "[IPv6:::ffff:192.168.2.1]");
+    }
+
+    #[test]
+    fn test_host_general() {
+        let email = EmailAddress::parse_with_options(
+            "simon@(comment)[x-tag:some-content]",
+            Options::default().with_general_literal().with_comments(),
+        )
+        .unwrap();
+
+        assert_eq!(
+            email.host(),
+            Host::General {
+                tag: "x-tag",
+                content: "some-content"
+            }
+        );
+        assert_eq!(email.host().ip_addr(), None);
+    }
+
+    #[test]
+    fn test_bad_a_label() {
+        expect(
+            "simon@xn--zz.example.com",
+            Error::InvalidALabel,
+            Some("invalid punycode"),
+        );
+        expect(
+            "simon@xn--abc-9.example.com",
+            Error::InvalidALabel,
+            Some("truncated punycode"),
+        );
+    }
+
+    #[test]
+    fn test_without_smtputf8() {
+        let options = Options::default().without_smtputf8();
+
+        valid_with_options("simon@example.com", options, None);
+        valid_with_options(
+            "simon@Sörensen.example.com",
+            options,
+            Some("U-label domain"),
+        );
+        valid_with_options(
+            "Dörte <simon@example.com>",
+            options,
+            Some("non-ASCII display name"),
+        );
+
+        expect_with_options(
+            "Dörte@example.com",
+            options,
+            Error::RequiresSmtpUtf8,
+            Some("non-ASCII local part"),
+        );
+        expect_with_options(
+            "\"Dörte Sörensen\"@example.com",
+            options,
+            Error::RequiresSmtpUtf8,
+            Some("non-ASCII quoted local part"),
+        );
+        expect_with_options(
+            "simon@[tag:Sörensen]",
+            options.with_general_literal(),
+            Error::RequiresSmtpUtf8,
+            Some("non-ASCII general literal"),
+        );
+        expect_with_options(
+            "simon@example\u{2028}.com",
+            options,
+            Error::InvalidCharacter,
+            Some("invalid character reported first"),
+        );
+    }
+
+    #[test]
+    fn test_idna_domains() {
+        let email = EmailAddress::from_str("Dörte@Sörensen.example.com").unwrap();
+
+        assert_eq!(
+            email.to_ascii_domain(),
+            Ok(String::from("xn--srensen-90a.example.com"))
+        );
+
+        let email = EmailAddress::from_str("Dörte@xn--srensen-90a.example.com").unwrap();
+
+        assert_eq!(
+            email.to_unicode_domain(),
+            Ok(String::from("sörensen.example.com"))
         );
+
+        let email = EmailAddress::from_str("simon@[127.0.0.1]").unwrap();
+
+        assert_eq!(email.to_ascii_domain(), Ok(String::from("[127.0.0.1]")));
+        assert_eq!(email.to_unicode_domain(), Ok(String::from("[127.0.0.1]")));
     }
 
+    #[cfg(feature = "idna_support")]
     #[test]
-    fn test_bad_example_20() {
-        expect_with_options(
-            "email@[::ffff:127.0.0.256]",
-            Options::default().without_domain_literal(),
-            Error::UnsupportedDomainLiteral,
-            Some("unsupported domain literal (4)"),
+    fn test_idna_tables() {
+        let email = EmailAddress::from_str("x@ｅｘａmple.com").unwrap();
+
+        assert_eq!(email.to_ascii_domain(), Ok(String::from("example.com")));
+
+        let email = EmailAddress::from_str("x@a\u{200D}b.com").unwrap();
+
+        assert_eq!(email.to_ascii_domain(), Err(Error::InvalidCharacter));
+
+        let options = Options::default().without_smtputf8();
+        assert!(EmailAddress::parse_with_options("x@ｅｘａmple.com", options).is_ok());
+        assert_eq!(
+            EmailAddress::parse_with_options("x@a\u{200D}b.com", options),
+            Err(Error::InvalidCharacter)
+        );
+        assert_eq!(
+            EmailAddress::parse_with_options("x@1\u{05D0}.com", options),
+            Err(Error::InvalidCharacter)
         );
     }
 
@@ -1878,6 +3748,37 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_parse_quoted_display_name() {
+        let email = EmailAddress::from_str("\"Doe, <John>\" <john@example.com>").unwrap();
+
+        assert_eq!(email.display_part(), "\"Doe, <John>\"");
+        assert_eq!(email.display_name(), "Doe, <John>");
+        assert_eq!(email.email(), "john@example.com");
+    }
+
+    #[test]
+    fn test_parse_display_name_no_space() {
+        let email = EmailAddress::from_str("John<john@example.com>").unwrap();
+
+        assert_eq!(email.display_part(), "John");
+        assert_eq!(email.email(), "john@example.com");
+    }
+
+    #[test]
+    fn test_parse_encoded_display_name() {
+        let email = EmailAddress::from_str(
+            "=?ISO-8859-1?Q?Keld_J=F8rn?= =?ISO-8859-1?Q?_Simonsen?= <keld@example.com>",
+        )
+        .unwrap();
+
+        assert_eq!(
+            email.display_part(),
+            "=?ISO-8859-1?Q?Keld_J=F8rn?= =?ISO-8859-1?Q?_Simonsen?="
+        );
+        assert_eq!(email.display_name(), "Keld Jørn Simonsen");
+    }
+
     #[test]
     // Regression test: GitHub issue #23
     fn test_missing_tld() {
@@ -1914,4 +3815,559 @@ mod tests {
         assert_eq!(email, EmailAddress::new_unchecked("simon@Example.com"));
         assert_eq!(email, EmailAddress::new_unchecked("simon@example.COM"));
     }
+
+    #[test]
+    fn test_eq_ignores_display_name() {
+        let email = EmailAddress::new_unchecked("simon@example.com");
+
+        assert_eq!(
+            email,
+            EmailAddress::new_unchecked("Simon <simon@example.com>")
+        );
+        assert_eq!(
+            EmailAddress::new_unchecked("Simon <simon@example.com>"),
+            EmailAddress::new_unchecked("\"S. Johnston\" <simon@example.com>")
+        );
+    }
+
+    #[test]
+    fn test_eq_quoted_local_part() {
+        let email = EmailAddress::new_unchecked("simon@example.com");
+
+        assert_eq!(email, EmailAddress::new_unchecked("\"simon\"@example.com"));
+        assert_eq!(
+            email,
+            EmailAddress::new_unchecked("\"si\\mon\"@example.com")
+        );
+        assert_eq!(
+            EmailAddress::new_unchecked("\"simon j\"@example.com"),
+            EmailAddress::new_unchecked("\"simon\\ j\"@example.com")
+        );
+        assert_ne!(email, EmailAddress::new_unchecked("\"Simon\"@example.com"));
+    }
+
+    #[test]
+    fn test_eq_ignores_comments() {
+        assert_eq!(
+            EmailAddress::new_unchecked("simon@example.com"),
+            EmailAddress::new_unchecked("simon(work)@Example.com (home)")
+        );
+    }
+
+    #[test]
+    fn test_ord_consistent_with_eq() {
+        let email = EmailAddress::new_unchecked("simon@example.com");
+
+        assert_eq!(
+            email.cmp(&EmailAddress::new_unchecked(
+                "Simon <\"simon\"@Example.COM>"
+            )),
+            Ordering::Equal
+        );
+        assert_eq!(
+            email.cmp(&EmailAddress::new_unchecked("simon@example.org")),
+            Ordering::Less
+        );
+        assert_eq!(
+            email.cmp(&EmailAddress::new_unchecked("Simon@example.com")),
+            Ordering::Greater
+        );
+    }
+
+    #[test]
+    fn test_cmp_by_domain() {
+        let mut addresses: Vec<EmailAddress> = [
+            "b@[127.0.0.1]",
+            "a@b.example.com",
+            "b@example.com",
+            "a@example.COM",
+            "a@a.example.com",
+            "a@example.co.uk",
+        ]
+        .iter()
+        .map(|s| EmailAddress::new_unchecked(*s))
+        .collect();
+
+        addresses.sort_by(EmailAddress::cmp_by_domain);
+
+        assert_eq!(
+            addresses
+                .iter()
+                .map(EmailAddress::as_str)
+                .collect::<Vec<_>>(),
+            vec![
+                "b@[127.0.0.1]",
+                "a@example.COM",
+                "b@example.com",
+                "a@a.example.com",
+                "a@b.example.com",
+                "a@example.co.uk",
+            ]
+        );
+        assert_eq!(addresses[1].cmp_domains(&addresses[2]), Ordering::Equal);
+    }
+
+    #[test]
+    fn test_hash_consistent_with_eq() {
+        use std::collections::HashSet;
+
+        let addresses: HashSet<EmailAddress> = [
+            "simon@example.com",
+            "simon@Example.COM",
+            "Simon <simon@example.com>",
+            "\"simon\"@example.com",
+            "\"si\\mon\"@example.com",
+            "simon(work)@Example.com (home)",
+            "Simon@example.com",
+            "\"Simon\"@EXAMPLE.com",
+        ]
+        .iter()
+        .map(|s| EmailAddress::new_unchecked(*s))
+        .collect();
+
+        assert_eq!(addresses.len(), 2);
+    }
+
+    #[test]
+    fn test_long_commented_local_part() {
+        let address = format!("(work) {}.,@example.com", "s".repeat(LOCAL_PART_MAX_LENGTH));
+        let options = Options::default().with_comments();
+
+        assert_eq!(
+            EmailAddress::parse_with_options(&address, options),
+            Err(Error::LocalPartTooLong)
+        );
+        assert_eq!(
+            EmailAddress::check_all(&address, options)
+                .into_iter()
+                .map(|finding| finding.error.kind)
+                .collect::<Vec<_>>(),
+            vec![Error::LocalPartTooLong, Error::InvalidCharacter]
+        );
+    }
+
+    #[test]
+    fn test_parts_recorded_by_parser() {
+        let options = Options::default().with_comments();
+        for address in &[
+            "simon@example.com",
+            "Simon <simon@example.com>",
+            "\"Simon\" <\"si@mon\"(work)@example.com>",
+            "simon(a@b)@[127.0.0.1]",
+        ] {
+            let email = EmailAddress::parse_with_options(address, options).unwrap();
+            assert_eq!(email.parts, Parts::new(address), "{:?}", address);
+        }
+    }
+
+    // ------------------------------------------------------------------------------------------------
+
+    #[test]
+    fn test_good_comments_01() {
+        valid_with_options(
+            "john(work)@example.com (John)",
+            Options::default().with_comments(),
+            Some("comments after local-part and domain"),
+        );
+    }
+
+    #[test]
+    fn test_good_comments_02() {
+        valid_with_options(
+            "(start)john.(middle)doe@(left)example.(right)com",
+            Options::default().with_comments(),
+            Some("comments around atoms and labels"),
+        );
+    }
+
+    #[test]
+    fn test_good_comments_03() {
+        valid_with_options(
+            "john(a (nested) \\) comment\\\\)@example.com",
+            Options::default().with_comments(),
+            Some("nested comments and quoted-pairs"),
+        );
+    }
+
+    #[test]
+    fn test_good_comments_04() {
+        valid_with_options(
+            "john (work)\r\n @ example.com\r\n (home@example.org)",
+            Options::default().with_comments(),
+            Some("folding white space"),
+        );
+    }
+
+    #[test]
+    fn test_good_comments_05() {
+        valid_with_options(
+            "\"john doe\"(work)@[127.0.0.1] (literal)",
+            Options::default().with_comments(),
+            Some("comments around quoted string and domain literal"),
+        );
+    }
+
+    #[test]
+    fn test_bad_comments_01() {
+        expect(
+            "john(work)@example.com",
+            Error::InvalidCharacter,
+            Some("comments not enabled"),
+        );
+    }
+
+    #[test]
+    fn test_bad_comments_02() {
+        expect_with_options(
+            "john(work@example.com",
+            Options::default().with_comments(),
+            Error::InvalidComment,
+            Some("unbalanced comment"),
+        );
+    }
+
+    #[test]
+    fn test_bad_comments_03() {
+        expect_with_options(
+            "jo(work)hn@example.com",
+            Options::default().with_comments(),
+            Error::InvalidComment,
+            Some("comment within an atom"),
+        );
+    }
+
+    #[test]
+    fn test_bad_comments_04() {
+        expect_with_options(
+            "john@example(work)com",
+            Options::default().with_comments(),
+            Error::InvalidComment,
+            Some("comment within a label"),
+        );
+    }
+
+    #[test]
+    fn test_bad_comments_05() {
+        expect_with_options(
+            "john(wo\rk)@example.com",
+            Options::default().with_comments(),
+            Error::InvalidComment,
+            Some("bare CR in comment"),
+        );
+    }
+
+    #[test]
+    fn test_bad_comments_06() {
+        expect_with_options(
+            "jo hn@example.com",
+            Options::default().with_comments(),
+            Error::InvalidCharacter,
+            Some("white space within an atom"),
+        );
+    }
+
+    #[test]
+    fn test_component_accessors() {
+        let email = EmailAddress::from_str("  \"Doe, John\" <\"j@d\"@example.com>").unwrap();
+        assert_eq!(email.display_part(), "\"Doe, John\"");
+        assert_eq!(email.local_part(), "\"j@d\"");
+        assert_eq!(email.domain(), "example.com");
+        assert_eq!(email.email(), "\"j@d\"@example.com");
+
+        let email = EmailAddress::from_str("j@example.com").unwrap();
+        assert_eq!(email.display_part(), "");
+        assert_eq!(email.email(), "j@example.com");
+    }
+
+    #[test]
+    fn test_new_unchecked_without_separator() {
+        let email = EmailAddress::new_unchecked("not an address");
+        assert_eq!(email.as_str(), "not an address");
+        assert_eq!(email.local_part(), "not an address");
+        assert_eq!(email.domain(), "");
+        assert_eq!(email.display_part(), "");
+    }
+
+    #[test]
+    fn test_subaddress() {
+        let email = EmailAddress::from_str("\"user+tag\\\"s\"@example.com").unwrap();
+        let (base, detail) = email.split_subaddress('+');
+        assert_eq!(base, "user");
+        assert_eq!(detail.unwrap(), "tag\"s");
+
+        let email = EmailAddress::from_str("user=tag@example.com").unwrap();
+        assert_eq!(email.subaddress('='), Some(Cow::Borrowed("tag")));
+
+        let email = EmailAddress::from_str("user+@example.com").unwrap();
+        assert_eq!(email.subaddress('+'), Some(Cow::Borrowed("")));
+
+        let email = EmailAddress::parse_with_options(
+            "\"user+tag\"(comment)@[127.0.0.1]",
+            Options::default().with_comments(),
+        )
+        .unwrap();
+        assert_eq!(
+            email.without_subaddress('+').unwrap().as_str(),
+            "user@[127.0.0.1]"
+        );
+
+        let email = EmailAddress::from_str("Simon <+tag@example.com>").unwrap();
+        assert_eq!(email.without_subaddress('+').unwrap(), email);
+        assert_eq!(
+            email.without_subaddress('+').unwrap().as_str(),
+            "Simon <+tag@example.com>"
+        );
+    }
+
+    #[test]
+    fn test_with_subaddress() {
+        let email = EmailAddress::from_str("simon@example.com").unwrap();
+
+        assert_eq!(
+            email.with_subaddress('-', "lists").unwrap().as_str(),
+            "simon-lists@example.com"
+        );
+        assert_eq!(
+            email.with_subaddress('+', "\"quoted\"").unwrap().as_str(),
+            "\"simon+\\\"quoted\\\"\"@example.com"
+        );
+        assert_eq!(
+            email.with_subaddress('+', "").unwrap().as_str(),
+            "simon+@example.com"
+        );
+        assert_eq!(
+            email.with_subaddress('+', &"x".repeat(64)),
+            Err(Error::LocalPartTooLong)
+        );
+    }
+
+    #[test]
+    fn test_with_domain() {
+        let email = EmailAddress::from_str("simon@example.com").unwrap();
+
+        assert_eq!(
+            email.with_domain("[127.0.0.1]").unwrap().as_str(),
+            "simon@[127.0.0.1]"
+        );
+        assert_eq!(
+            email.with_domain("example.com (c)"),
+            Err(Error::InvalidCharacter)
+        );
+        assert_eq!(
+            email.with_domain("[x-tag:foo]"),
+            Err(Error::InvalidIPAddress)
+        );
+
+        let email = EmailAddress::parse_with_options(
+            "simon(work)@[x-tag:foo]",
+            Options::default().with_comments().with_general_literal(),
+        )
+        .unwrap();
+        assert_eq!(
+            email.with_local_part("jane").unwrap().as_str(),
+            "jane@[x-tag:foo]"
+        );
+    }
+
+    #[test]
+    fn test_strip_comments() {
+        let email = EmailAddress::parse_with_options(
+            "John <john.(middle)doe(work)@example.com (home)>",
+            Options::default().with_comments(),
+        )
+        .unwrap();
+
+        assert_eq!(email.local_part(), "john.(middle)doe(work)");
+        assert_eq!(email.domain(), "example.com (home)");
+        assert_eq!(
+            email.strip_comments().as_str(),
+            "John <john.doe@example.com>"
+        );
+    }
+}
+
+#[cfg(test)]
+mod proptests {
+    use super::*;
+    use proptest::prelude::*;
+    use std::collections::hash_map::DefaultHasher;
+    use std::hash::Hasher;
+
+    fn hash_of(email: &EmailAddress) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        email.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    fn local_part() -> impl Strategy<Value = String> {
+        "[a-zA-Z0-9!#$%&'*+/=?^_`{|}~-]{1,10}(\\.[a-zA-Z0-9_-]{1,10}){0,2}"
+    }
+
+    fn domain() -> impl Strategy<Value = String> {
+        "[a-zA-Z0-9]{1,10}(\\.[a-zA-Z0-9]{1,10}){0,3}"
+    }
+
+    fn address() -> impl Strategy<Value = String> {
+        (
+            prop::option::of("[a-zA-Z ]{1,10}"),
+            prop::bool::ANY,
+            local_part(),
+            domain(),
+        )
+            .prop_map(|(display, quoted, local_part, domain)| {
+                let local_part = if quoted {
+                    format!("\"{}\"", local_part)
+                } else {
+                    local_part
+                };
+                match display {
+                    Some(display) if !display.trim().is_empty() => {
+                        format!("{} <{}@{}>", display.trim(), local_part, domain)
+                    }
+                    _ => format!("{}@{}", local_part, domain),
+                }
+            })
+    }
+
+    fn write_address(display: Option<&str>, local_part: &str, domain: &str) -> String {
+        match display {
+            Some(display) => format!("{} <{}@{}>", display, local_part, domain),
+            None => format!("{}@{}", local_part, domain),
+        }
+    }
+
+    fn quote(local_part: &str, escaped: &[bool]) -> String {
+        let mut quoted = String::from("\"");
+        for (c, escape) in local_part.chars().zip(escaped.iter().cycle()) {
+            if *escape {
+                quoted.push('\\');
+            }
+            quoted.push(c);
+        }
+        quoted.push('"');
+        quoted
+    }
+
+    ///
+    /// A pair of addresses that are equal but written differently; the second has the case of
+    /// its domain changed, its `local-part` quoted or unquoted with some characters written as
+    /// quoted-pairs, and its display name added, removed, or changed.
+    ///
+    fn equal_addresses() -> impl Strategy<Value = (String, String)> {
+        (
+            prop::option::of("[a-zA-Z]{1,10}( [a-zA-Z]{1,10})?"),
+            prop::option::of("[a-zA-Z]{1,10}"),
+            local_part(),
+            prop::bool::ANY,
+            prop::bool::ANY,
+            prop::collection::vec(prop::bool::ANY, 1..8),
+            domain(),
+            prop::collection::vec(prop::bool::ANY, 1..8),
+        )
+            .prop_map(
+                |(
+                    display,
+                    other_display,
+                    local_part,
+                    quoted,
+                    other_quoted,
+                    escaped,
+                    domain,
+                    upper,
+                )| {
+                    let left_local_part = if quoted {
+                        quote(&local_part, &[false])
+                    } else {
+                        local_part.clone()
+                    };
+                    let right_local_part = if other_quoted {
+                        quote(&local_part, &escaped)
+                    } else {
+                        local_part
+                    };
+                    let right_domain: String = domain
+                        .chars()
+                        .zip(upper.iter().cycle())
+                        .map(|(c, upper)| {
+                            if *upper {
+                                c.to_ascii_uppercase()
+                            } else {
+                                c.to_ascii_lowercase()
+                            }
+                        })
+                        .collect();
+                    (
+                        write_address(display.as_deref(), &left_local_part, &domain),
+                        write_address(other_display.as_deref(), &right_local_part, &right_domain),
+                    )
+                },
+            )
+    }
+
+    proptest! {
+        #[test]
+        fn equal_addresses_have_equal_hashes((left, right) in equal_addresses()) {
+            let left = EmailAddress::from_str(&left).unwrap();
+            let right = EmailAddress::from_str(&right).unwrap();
+            prop_assert_eq!(&left, &right);
+            prop_assert_eq!(hash_of(&left), hash_of(&right));
+        }
+
+        #[test]
+        fn eq_is_reflexive_and_symmetric(left in address(), right in address()) {
+            let left = EmailAddress::from_str(&left).unwrap();
+            let right = EmailAddress::from_str(&right).unwrap();
+            prop_assert_eq!(&left, &left.clone());
+            prop_assert_eq!(left == right, right == left);
+        }
+
+        #[test]
+        fn equivalent_forms_are_equal(
+            display in "[a-zA-Z]{1,10}",
+            local_part in local_part(),
+            domain in domain(),
+        ) {
+            let email = EmailAddress::from_str(&format!("{}@{}", local_part, domain)).unwrap();
+            let variants = [
+                format!("{}@{}", local_part, domain.to_uppercase()),
+                format!("{}@{}", local_part, domain.to_lowercase()),
+                format!("\"{}\"@{}", local_part, domain),
+                format!("{} <{}@{}>", display, local_part, domain),
+            ];
+            for variant in variants.iter() {
+                let variant = EmailAddress::from_str(variant).unwrap();
+                prop_assert_eq!(&email, &variant);
+                prop_assert_eq!(hash_of(&email), hash_of(&variant));
+            }
+        }
+
+        #[test]
+        fn ord_is_consistent_with_eq(
+            (left, right) in prop_oneof![equal_addresses(), (address(), address())],
+        ) {
+            let left = EmailAddress::from_str(&left).unwrap();
+            let right = EmailAddress::from_str(&right).unwrap();
+            prop_assert_eq!(left.cmp(&right) == Ordering::Equal, left == right);
+            prop_assert_eq!(left.cmp_by_domain(&right) == Ordering::Equal, left == right);
+            prop_assert_eq!(left.cmp(&right), right.cmp(&left).reverse());
+            prop_assert_eq!(left.partial_cmp(&right), Some(left.cmp(&right)));
+        }
+
+        #[test]
+        fn local_part_is_case_sensitive(local_part in "[a-z]{1,10}", domain in domain()) {
+            let email = EmailAddress::from_str(&format!("{}@{}", local_part, domain)).unwrap();
+            let upper = EmailAddress::from_str(
+                &format!("{}@{}", local_part.to_uppercase(), domain)
+            ).unwrap();
+            prop_assert_ne!(email, upper);
+        }
+
+        #[test]
+        fn quoted_local_part_round_trips(value in "[ -~]{1,20}", domain in domain()) {
+            let local_part = EmailAddress::quote_local_part(&value);
+            let email = EmailAddress::from_str(&format!("{}@{}", local_part, domain)).unwrap();
+            prop_assert_eq!(email.local_part(), local_part.as_ref());
+            prop_assert_eq!(email.local_part_unquoted(), value.as_str());
+        }
+    }
 }
@@ -0,0 +1,671 @@
+/*!
+Support for parsing the RFC 5322 `address-list` found in the `To:`, `Cc:`, and similar header
+fields, which may contain both mailboxes and named groups of mailboxes.
+
+From RFC 5322, §3.4. [Address Specification](https://tools.ietf.org/html/rfc5322#section-3.4):
+
+```ebnf
+address         =   mailbox / group
+
+mailbox         =   name-addr / addr-spec
+
+group           =   display-name ":" [group-list] ";" [CFWS]
+
+mailbox-list    =   (mailbox *("," mailbox)) / obs-mbox-list
+
+address-list    =   (address *("," address)) / obs-addr-list
+
+group-list      =   mailbox-list / CFWS / obs-group-list
+```
+*/
+
+use crate::{
+    decode_display_name, encode_display_name, is_phrase, parse_address, strip_cfws, EmailAddress,
+    Error, Options,
+};
+use crate::{DISPLAY_END, DISPLAY_START, DQUOTE, ESC, LBRACKET, LPAREN, RBRACKET, RPAREN};
+use std::borrow::Cow;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+// ------------------------------------------------------------------------------------------------
+// Public Types
+// ------------------------------------------------------------------------------------------------
+
+///
+/// A single entry in an [AddressList], either an individual mailbox or a named group of
+/// mailboxes.
+///
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Address {
+    /// A single mailbox, with or without a display name.
+    Mailbox(EmailAddress),
+    /// A named, possibly empty, group of mailboxes.
+    Group(Group),
+}
+
+///
+/// A named group of mailboxes, such as `team: a@example.com, b@example.com;`, or the commonly
+/// used empty group `Undisclosed recipients:;`.
+///
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Group {
+    display_part: String,
+    members: Vec<EmailAddress>,
+}
+
+///
+/// Type representing the value of an address header such as `To:` or `Cc:`, a comma-separated
+/// list of mailboxes and groups. Each mailbox is parsed in the same manner as
+/// `EmailAddress::parse_with_options`, except that an `angle-addr` without a display name,
+/// such as `<simon@example.com>`, is also accepted. RFC 5322 requires at least one address, so
+/// a list with none returns `Error::AddressListEmpty`.
+///
+/// ```rust
+/// use email_address::*;
+/// use std::str::FromStr;
+///
+/// let list = AddressList::from_str(
+///     "\"Doe, John\" <john@example.com>, team: a@example.com, b@example.com;"
+/// ).unwrap();
+///
+/// assert_eq!(list.len(), 2);
+/// assert_eq!(list.mailboxes().count(), 3);
+/// ```
+///
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct AddressList(Vec<Address>);
+
+// ------------------------------------------------------------------------------------------------
+// Implementations
+// ------------------------------------------------------------------------------------------------
+
+const COMMA: char = ',';
+const COLON: char = ':';
+const SEMICOLON: char = ';';
+
+// ------------------------------------------------------------------------------------------------
+
+impl Display for Address {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Address::Mailbox(mailbox) => write!(f, "{}", mailbox),
+            Address::Group(group) => write!(f, "{}", group),
+        }
+    }
+}
+
+impl From<EmailAddress> for Address {
+    fn from(mailbox: EmailAddress) -> Self {
+        Address::Mailbox(mailbox)
+    }
+}
+
+impl From<Group> for Address {
+    fn from(group: Group) -> Self {
+        Address::Group(group)
+    }
+}
+
+impl Address {
+    ///
+    /// Returns `true` if this address is a group, else `false`.
+    ///
+    pub fn is_group(&self) -> bool {
+        matches!(self, Address::Group(_))
+    }
+
+    ///
+    /// Returns all the mailboxes for this address; either the single mailbox, or all the
+    /// members of the group.
+    ///
+    pub fn mailboxes(&self) -> &[EmailAddress] {
+        match self {
+            Address::Mailbox(mailbox) => std::slice::from_ref(mailbox),
+            Address::Group(group) => group.members(),
+        }
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl Display for Group {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}{}", self.display_part, COLON)?;
+        for (i, member) in self.members.iter().enumerate() {
+            if i == 0 {
+                write!(f, " {}", member)?;
+            } else {
+                write!(f, "{} {}", COMMA, member)?;
+            }
+        }
+        write!(f, "{}", SEMICOLON)
+    }
+}
+
+impl Group {
+    ///
+    /// Create a new group with the given name and members. The name is quoted or encoded as
+    /// required to write it as an RFC 5322 `phrase`.
+    ///
+    /// ```rust
+    /// use email_address::*;
+    ///
+    /// let group = Group::new("Undisclosed recipients", Vec::default());
+    ///
+    /// assert_eq!(group.to_string(), "Undisclosed recipients:;");
+    ///
+    /// let group = Group::new("Doe, John", Vec::default());
+    ///
+    /// assert_eq!(group.to_string(), "\"Doe, John\":;");
+    /// assert_eq!(group.name(), "Doe, John");
+    /// ```
+    ///
+    pub fn new<S>(name: S, members: Vec<EmailAddress>) -> Self
+    where
+        S: AsRef<str>,
+    {
+        Self {
+            display_part: encode_display_name(name.as_ref(), false).into_owned(),
+            members,
+        }
+    }
+
+    ///
+    /// Returns the display name of this group, decoded from the raw `display_part` in the
+    /// same manner as `EmailAddress::display_name`.
+    ///
+    /// ```rust
+    /// use email_address::*;
+    /// use std::str::FromStr;
+    ///
+    /// let list = AddressList::from_str("\"Team \\\"A\\\"\" (the team): a@example.com;").unwrap();
+    /// let group = match list.iter().next().unwrap() {
+    ///     Address::Group(group) => group,
+    ///     _ => unreachable!(),
+    /// };
+    ///
+    /// assert_eq!(group.display_part(), "\"Team \\\"A\\\"\" (the team)");
+    /// assert_eq!(group.name(), "Team \"A\"");
+    /// ```
+    ///
+    pub fn name(&self) -> Cow<'_, str> {
+        decode_display_name(&self.display_part)
+    }
+
+    ///
+    /// Returns the display name of this group as written, without any decoding.
+    ///
+    pub fn display_part(&self) -> &str {
+        &self.display_part
+    }
+
+    ///
+    /// Returns the mailboxes that are members of this group.
+    ///
+    pub fn members(&self) -> &[EmailAddress] {
+        &self.members
+    }
+
+    ///
+    /// Returns `true` if this group has no members, else `false`.
+    ///
+    pub fn is_empty(&self) -> bool {
+        self.members.is_empty()
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl Display for AddressList {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for (i, address) in self.0.iter().enumerate() {
+            if i > 0 {
+                write!(f, "{} ", COMMA)?;
+            }
+            write!(f, "{}", address)?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for AddressList {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse_address_list(s, Default::default())
+    }
+}
+
+impl From<Vec<Address>> for AddressList {
+    fn from(addresses: Vec<Address>) -> Self {
+        Self(addresses)
+    }
+}
+
+impl From<AddressList> for Vec<Address> {
+    fn from(list: AddressList) -> Self {
+        list.0
+    }
+}
+
+impl IntoIterator for AddressList {
+    type Item = Address;
+    type IntoIter = std::vec::IntoIter<Address>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a AddressList {
+    type Item = &'a Address;
+    type IntoIter = std::slice::Iter<'a, Address>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.iter()
+    }
+}
+
+impl AddressList {
+    ///
+    /// Parses an [AddressList] with custom [Options], which are applied to each mailbox in
+    /// the list.
+    ///
+    /// ```rust
+    /// use email_address::*;
+    ///
+    /// assert_eq!(
+    ///     AddressList::parse_with_options(
+    ///         "Simon <simon@example.com>, jane@example.com",
+    ///         Options::default().without_display_text(),
+    ///     ),
+    ///     Err(Error::UnsupportedDisplayName)
+    /// );
+    /// ```
+    ///
+    pub fn parse_with_options(text: &str, options: Options) -> Result<Self, Error> {
+        parse_address_list(text, options)
+    }
+
+    ///
+    /// Returns the number of addresses, mailboxes and groups, in this list.
+    ///
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    ///
+    /// Returns `true` if this list contains no addresses, else `false`.
+    ///
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    ///
+    /// Returns an iterator over the addresses, mailboxes and groups, in this list.
+    ///
+    pub fn iter(&self) -> std::slice::Iter<'_, Address> {
+        self.0.iter()
+    }
+
+    ///
+    /// Returns an iterator over all the mailboxes in this list, including the members of
+    /// any groups.
+    ///
+    /// ```rust
+    /// use email_address::*;
+    /// use std::str::FromStr;
+    ///
+    /// let list = AddressList::from_str(
+    ///     "simon@example.com, Undisclosed recipients:;, team: jane@example.com;"
+    /// ).unwrap();
+    ///
+    /// assert_eq!(
+    ///     list.mailboxes().map(EmailAddress::as_str).collect::<Vec<_>>(),
+    ///     vec!["simon@example.com", "jane@example.com"]
+    /// );
+    /// ```
+    ///
+    pub fn mailboxes(&self) -> impl Iterator<Item = &EmailAddress> {
+        self.0.iter().flat_map(Address::mailboxes)
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Functions
+// ------------------------------------------------------------------------------------------------
+
+fn parse_address_list(text: &str, options: Options) -> Result<AddressList, Error> {
+    let mut addresses: Vec<Address> = Vec::default();
+    let mut group: Option<Group> = None;
+    let mut group_ended = false;
+    let mut start = 0;
+
+    for (index, delimiter) in find_delimiters(text) {
+        let item = &text[start..index];
+        start = index + 1;
+        match delimiter {
+            COMMA if group_ended => {
+                parse_group_end(item, options)?;
+                group_ended = false;
+            }
+            COMMA => match &mut group {
+                None => {
+                    if let Some(mailbox) = parse_mailbox(item, options)? {
+                        addresses.push(mailbox.into());
+                    }
+                }
+                Some(group) => {
+                    if let Some(mailbox) = parse_mailbox(item, options)? {
+                        group.members.push(mailbox);
+                    }
+                }
+            },
+            COLON => {
+                let name = item.trim();
+                if group.is_some() || group_ended || !is_phrase(name) {
+                    return Error::InvalidGroup.into();
+                }
+                group = Some(Group {
+                    display_part: name.to_string(),
+                    members: Vec::default(),
+                });
+            }
+            _ => match group.take() {
+                None => return Error::InvalidGroup.into(),
+                Some(mut group) => {
+                    if let Some(mailbox) = parse_mailbox(item, options)? {
+                        group.members.push(mailbox);
+                    }
+                    addresses.push(group.into());
+                    group_ended = true;
+                }
+            },
+        }
+    }
+
+    let item = &text[start..];
+    if group.is_some() {
+        return Error::InvalidGroup.into();
+    } else if group_ended {
+        parse_group_end(item, options)?;
+    } else if let Some(mailbox) = parse_mailbox(item, options)? {
+        addresses.push(mailbox.into());
+    }
+
+    if addresses.is_empty() {
+        Error::AddressListEmpty.into()
+    } else {
+        Ok(AddressList(addresses))
+    }
+}
+
+///
+/// Parse a single mailbox from the list, empty items are allowed by the obsolete syntax and
+/// are skipped, as are items of only `CFWS` if comments are allowed. A `name-addr` without a
+/// display name is stored as its `addr-spec`.
+///
+fn parse_mailbox(item: &str, options: Options) -> Result<Option<EmailAddress>, Error> {
+    let item = item.trim();
+    if item.is_empty() || is_cfws(item, options) {
+        Ok(None)
+    } else if let Some(addr_spec) = item
+        .strip_prefix(DISPLAY_START)
+        .and_then(|item| item.strip_suffix(DISPLAY_END))
+    {
+        let options = Options {
+            allow_display_text: false,
+            ..options
+        };
+        parse_address(addr_spec.trim(), options).map(Some)
+    } else {
+        parse_address(item, options).map(Some)
+    }
+}
+
+///
+/// Returns `true` if `item` is only `CFWS`, which is only possible if comments are allowed.
+///
+fn is_cfws(item: &str, options: Options) -> bool {
+    options.allow_comments
+        && item.starts_with(LPAREN)
+        && matches!(strip_cfws(item), Ok(stripped) if stripped.is_empty())
+}
+
+///
+/// Only `CFWS` is allowed between the `;` that ends a group and the following `,`.
+///
+fn parse_group_end(item: &str, options: Options) -> Result<(), Error> {
+    let item = item.trim();
+    if item.is_empty() || (options.allow_comments && strip_cfws(item)?.is_empty()) {
+        Ok(())
+    } else {
+        Error::InvalidGroup.into()
+    }
+}
+
+///
+/// Find the indices of all the `,`, `:`, and `;` delimiters that are not within a quoted
+/// string, comment, angle brackets, or domain literal.
+///
+fn find_delimiters(text: &str) -> Vec<(usize, char)> {
+    let mut delimiters = Vec::default();
+    let mut in_quotes = false;
+    let mut in_angle = false;
+    let mut in_literal = false;
+    let mut depth = 0_usize;
+    let mut char_iter = text.char_indices();
+    while let Some((index, c)) = char_iter.next() {
+        match c {
+            ESC if in_quotes || depth > 0 => {
+                let _ = char_iter.next();
+            }
+            DQUOTE if depth == 0 => in_quotes = !in_quotes,
+            _ if in_quotes => (),
+            LPAREN => depth += 1,
+            RPAREN if depth > 0 => depth -= 1,
+            _ if depth > 0 => (),
+            LBRACKET => in_literal = true,
+            RBRACKET => in_literal = false,
+            _ if in_literal => (),
+            DISPLAY_START => in_angle = true,
+            DISPLAY_END => in_angle = false,
+            COMMA | COLON | SEMICOLON if !in_angle => delimiters.push((index, c)),
+            _ => (),
+        }
+    }
+    delimiters
+}
+
+// ------------------------------------------------------------------------------------------------
+// Unit Tests
+// ------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mailboxes(text: &str) -> Vec<String> {
+        let list = AddressList::from_str(text).unwrap();
+        list.mailboxes().map(EmailAddress::to_string).collect()
+    }
+
+    #[test]
+    fn test_single_mailbox() {
+        assert_eq!(mailboxes("simon@example.com"), vec!["simon@example.com"]);
+    }
+
+    #[test]
+    fn test_multiple_mailboxes() {
+        assert_eq!(
+            mailboxes("simon@example.com,\r\n Jane <jane@example.com> , john@example.org"),
+            vec![
+                "simon@example.com",
+                "Jane <jane@example.com>",
+                "john@example.org"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_bare_angle_addr() {
+        assert_eq!(mailboxes("<simon@example.com>"), vec!["simon@example.com"]);
+        assert_eq!(
+            mailboxes("simon@example.com, < jane@example.com >"),
+            vec!["simon@example.com", "jane@example.com"]
+        );
+        assert_eq!(
+            AddressList::from_str("<Jane <jane@example.com>>"),
+            Err(Error::UnsupportedDisplayName)
+        );
+    }
+
+    #[test]
+    fn test_comma_in_quoted_display_name() {
+        assert_eq!(
+            mailboxes("\"Doe, John\" <john@example.com>, \"a,b\"@example.com"),
+            vec!["\"Doe, John\" <john@example.com>", "\"a,b\"@example.com"]
+        );
+    }
+
+    #[test]
+    fn test_empty_items_skipped() {
+        assert_eq!(
+            mailboxes(", simon@example.com,, jane@example.com,"),
+            vec!["simon@example.com", "jane@example.com"]
+        );
+    }
+
+    #[test]
+    fn test_empty_group() {
+        let list = AddressList::from_str("Undisclosed recipients:;").unwrap();
+
+        assert_eq!(list.len(), 1);
+        match list.iter().next().unwrap() {
+            Address::Group(group) => {
+                assert_eq!(group.name(), "Undisclosed recipients");
+                assert!(group.is_empty());
+            }
+            _ => panic!("expected a group"),
+        }
+    }
+
+    #[test]
+    fn test_comment_only_group() {
+        let list = AddressList::parse_with_options(
+            "Undisclosed recipients:(none);",
+            Options::default().with_comments(),
+        )
+        .unwrap();
+
+        assert_eq!(list.len(), 1);
+        assert_eq!(list.mailboxes().count(), 0);
+        assert!(AddressList::from_str("Undisclosed recipients:(none);").is_err());
+    }
+
+    #[test]
+    fn test_groups_and_mailboxes() {
+        let list = AddressList::from_str(
+            "simon@example.com, team: a@example.com, B <b@example.com>;, jane@example.com",
+        )
+        .unwrap();
+
+        assert_eq!(list.len(), 3);
+        assert!(list.iter().nth(1).unwrap().is_group());
+        assert_eq!(
+            list.to_string(),
+            "simon@example.com, team: a@example.com, B <b@example.com>;, jane@example.com"
+        );
+        assert_eq!(list.mailboxes().count(), 4);
+    }
+
+    #[test]
+    fn test_group_with_comment() {
+        let list = AddressList::parse_with_options(
+            "team: a@example.com; (the team), jane@example.com",
+            Options::default().with_comments(),
+        )
+        .unwrap();
+
+        assert_eq!(list.len(), 2);
+    }
+
+    #[test]
+    fn test_literal_in_list() {
+        assert_eq!(
+            mailboxes("a@[IPv6:2001:db8::1], b@[127.0.0.1]"),
+            vec!["a@[IPv6:2001:db8::1]", "b@[127.0.0.1]"]
+        );
+    }
+
+    #[test]
+    fn test_bad_groups() {
+        assert_eq!(
+            AddressList::from_str("team: a@example.com"),
+            Err(Error::InvalidGroup)
+        );
+        assert_eq!(
+            AddressList::from_str("a@example.com;"),
+            Err(Error::InvalidGroup)
+        );
+        assert_eq!(
+            AddressList::from_str(": a@example.com;"),
+            Err(Error::InvalidGroup)
+        );
+        assert_eq!(
+            AddressList::from_str("team: inner: a@example.com;;"),
+            Err(Error::InvalidGroup)
+        );
+        assert_eq!(
+            AddressList::from_str("team: a@example.com; b@example.com"),
+            Err(Error::InvalidGroup)
+        );
+        assert_eq!(
+            AddressList::from_str("a@b.com: x@y.com;"),
+            Err(Error::InvalidGroup)
+        );
+        assert_eq!(
+            AddressList::from_str("<team>: x@y.com;"),
+            Err(Error::InvalidGroup)
+        );
+    }
+
+    #[test]
+    fn test_phrase_group_names() {
+        for name in &["\"The Team\"", "J. Q. Public (team)", "=?UTF-8?Q?Team?="] {
+            let list = AddressList::from_str(&format!("{}: a@example.com;", name)).unwrap();
+            assert_eq!(list.len(), 1, "{:?}", name);
+        }
+    }
+
+    #[test]
+    fn test_encoded_group_name() {
+        let list = AddressList::from_str("=?UTF-8?B?S8O2bmln?= =?UTF-8?Q?_Team?=: a@example.com;")
+            .unwrap();
+        match list.iter().next().unwrap() {
+            Address::Group(group) => {
+                assert_eq!(
+                    group.display_part(),
+                    "=?UTF-8?B?S8O2bmln?= =?UTF-8?Q?_Team?="
+                );
+                assert_eq!(group.name(), "König Team");
+            }
+            _ => panic!("expected a group"),
+        }
+    }
+
+    #[test]
+    fn test_empty_list() {
+        assert_eq!(AddressList::from_str(""), Err(Error::AddressListEmpty));
+        assert_eq!(AddressList::from_str(" , ,"), Err(Error::AddressListEmpty));
+    }
+
+    #[test]
+    fn test_bad_mailbox() {
+        assert_eq!(
+            AddressList::from_str("simon@example.com, Abc.example.com"),
+            Err(Error::MissingSeparator)
+        );
+    }
+}
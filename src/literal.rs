@@ -0,0 +1,378 @@
+/*!
+Support for addresses that are validated at compile time.
+
+The [email!](crate::email!) macro, and the `const fn` [EmailAddressRef::from_static], validate
+an address with the grammar used by `EmailAddress::from_str`, that is with the default
+[Options](crate::Options), so that an invalid address literal fails the build with the message
+of the corresponding [Error]. The result is an `EmailAddressRef<'static>`, which may be used to
+initialize a `const` or `static`.
+
+```rust
+use email_address::*;
+
+static POSTMASTER: EmailAddressRef<'static> = email!("postmaster@example.com");
+
+assert_eq!(POSTMASTER.local_part(), "postmaster");
+assert_eq!(POSTMASTER.to_owned().domain(), "example.com");
+```
+
+An invalid literal does not compile.
+
+```rust,compile_fail
+use email_address::*;
+
+static POSTMASTER: EmailAddressRef<'static> = email!("postmaster@example..com");
+```
+
+Some addresses that are valid at runtime are not supported at compile time, as checking them
+requires the Unicode tables or IDNA processing used at runtime; these are listed in the
+documentation of [email!](crate::email!).
+*/
+
+use crate::{
+    grammar, is_dtext_char, EmailAddressRef, Error, Parts, DOMAIN_MAX_LENGTH, LOCAL_PART_MAX_LENGTH,
+};
+
+// ------------------------------------------------------------------------------------------------
+// Public Macros
+// ------------------------------------------------------------------------------------------------
+
+///
+/// Construct an `EmailAddressRef<'static>` from a string literal which is validated at compile
+/// time, as `EmailAddressRef::from_static`; an invalid address fails the build.
+///
+/// ```rust
+/// use email_address::*;
+///
+/// const NO_REPLY: EmailAddressRef<'static> = email!("Example <noreply@example.com>");
+///
+/// assert_eq!(NO_REPLY.display_part(), "Example");
+/// assert_eq!(NO_REPLY.email(), "noreply@example.com");
+/// ```
+///
+/// The following addresses are valid at runtime but are not supported at compile time, and fail
+/// the build with a message saying so; they should instead be parsed at runtime with
+/// `EmailAddressRef::parse_with_options` or `EmailAddressRef::try_from`.
+///
+/// * A domain with a label that begins or ends with a non-ASCII character, such as
+///   `simon@例子.广告`, as whether the character is a letter or digit depends on the
+///   Unicode tables.
+/// * A domain with an IDNA A-label, such as `simon@xn--bcher-kva.example`, as validating the
+///   label requires decoding it.
+///
+/// ```rust,compile_fail
+/// use email_address::*;
+///
+/// const SHOP: EmailAddressRef<'static> = email!("simon@xn--bcher-kva.example");
+/// ```
+///
+#[macro_export]
+macro_rules! email {
+    ($address:expr) => {{
+        const EMAIL: $crate::EmailAddressRef<'static> =
+            $crate::EmailAddressRef::from_static($address);
+        EMAIL
+    }};
+}
+
+// ------------------------------------------------------------------------------------------------
+// Implementations
+// ------------------------------------------------------------------------------------------------
+
+impl EmailAddressRef<'static> {
+    ///
+    /// Creates an `EmailAddressRef` from a static string, validated with the default
+    /// [Options](crate::Options). This is a `const fn`, so when used to initialize a `const` or
+    /// `static` an invalid address fails the build with the message of the [Error]; when
+    /// called at runtime an invalid address panics.
+    ///
+    /// ```rust
+    /// use email_address::*;
+    ///
+    /// static ABUSE: EmailAddressRef<'static> = EmailAddressRef::from_static("abuse@example.com");
+    ///
+    /// assert_eq!(ABUSE.domain(), "example.com");
+    /// ```
+    ///
+    pub const fn from_static(address: &'static str) -> Self {
+        match check_static(address) {
+            Checked::Valid(parts) => Self::from_parts(address, parts),
+            Checked::Invalid(error) => panic!("{}", error.message()),
+            Checked::Unsupported => panic!(
+                "A domain label beginning or ending with a non-ASCII character, or an IDNA \
+                A-label, cannot be validated at compile time."
+            ),
+        }
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Types
+// ------------------------------------------------------------------------------------------------
+
+///
+/// The result of checking an address at compile time.
+///
+enum Checked {
+    Valid(Parts),
+    Invalid(Error),
+    Unsupported,
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Functions
+// ------------------------------------------------------------------------------------------------
+
+// The following functions compose the productions in the `grammar` module, which are shared with
+// the runtime parser, as the runtime parser does with the default options.
+
+const fn check_static(address: &str) -> Checked {
+    let text = address.as_bytes();
+    let (display_start, display_end, email_start, email_end) =
+        match grammar::split_display_email(text, 0, text.len()) {
+            Ok(bounds) => bounds,
+            Err(error) => return Checked::Invalid(error),
+        };
+    let separator = match grammar::find_separator(text, email_start, email_end) {
+        Some(separator) => separator,
+        None => return Checked::Invalid(Error::MissingSeparator),
+    };
+
+    if display_start == display_end && separator > email_start && text[email_start] == b'<' {
+        return Checked::Invalid(Error::MissingDisplayName);
+    }
+    if let Err(error) = check_local_part(text, email_start, separator) {
+        return Checked::Invalid(error);
+    }
+    match check_domain(text, separator + 1, email_end) {
+        Checked::Valid(_) => Checked::Valid(Parts {
+            display_start,
+            display_end,
+            local_start: email_start,
+            separator,
+            domain_start: separator + 1,
+            domain_end: email_end,
+        }),
+        checked => checked,
+    }
+}
+
+const fn check_local_part(text: &[u8], start: usize, end: usize) -> Result<(), Error> {
+    let len = end - start;
+    if len == 0 {
+        Err(Error::LocalPartEmpty)
+    } else if len > LOCAL_PART_MAX_LENGTH {
+        Err(Error::LocalPartTooLong)
+    } else {
+        grammar::local_part(text, start, end)
+    }
+}
+
+const fn check_domain(text: &[u8], start: usize, end: usize) -> Checked {
+    let len = end - start;
+    if len == 0 {
+        Checked::Invalid(Error::DomainEmpty)
+    } else if len > DOMAIN_MAX_LENGTH {
+        Checked::Invalid(Error::DomainTooLong)
+    } else if text[start] == b'[' && text[end - 1] == b']' {
+        match check_literal_domain(text, start + 1, end - 1) {
+            Ok(()) => Checked::Valid(EMPTY_PARTS),
+            Err(error) => Checked::Invalid(error),
+        }
+    } else {
+        let mut label_start = start;
+        let mut index = start;
+        while index <= end {
+            if index == end || text[index] == b'.' {
+                match check_sub_domain(text, label_start, index) {
+                    Checked::Valid(_) => (),
+                    checked => return checked,
+                }
+                label_start = index + 1;
+            }
+            index += 1;
+        }
+        Checked::Valid(EMPTY_PARTS)
+    }
+}
+
+///
+/// Check a domain label; `grammar::sub_domain` leaves the checks which need the Unicode tables
+/// or IDNA to the caller, so these labels are unsupported.
+///
+const fn check_sub_domain(text: &[u8], start: usize, end: usize) -> Checked {
+    if start < end && (!text[start].is_ascii() || !text[end - 1].is_ascii()) {
+        return Checked::Unsupported;
+    }
+    if let Err(error) = grammar::sub_domain(text, start, end) {
+        return Checked::Invalid(error);
+    }
+    if end - start >= 4
+        && text[start].eq_ignore_ascii_case(&b'x')
+        && text[start + 1].eq_ignore_ascii_case(&b'n')
+        && text[start + 2] == b'-'
+        && text[start + 3] == b'-'
+    {
+        return Checked::Unsupported;
+    }
+    Checked::Valid(EMPTY_PARTS)
+}
+
+const fn check_literal_domain(text: &[u8], start: usize, end: usize) -> Result<(), Error> {
+    let mut index = start;
+    let mut ipv4 = true;
+    while index < end {
+        let b = text[index];
+        if !is_dtext_char(b as char) {
+            return Err(Error::InvalidCharacter);
+        }
+        ipv4 = ipv4 && (b.is_ascii_digit() || b == b'.');
+        index += 1;
+    }
+    let valid = if grammar::has_ipv6_tag(text, start, end) {
+        grammar::ipv6_address(text, start + 5, end).is_some()
+    } else {
+        ipv4 && grammar::ipv4_address(text, start, end).is_some()
+    };
+    if valid {
+        Ok(())
+    } else {
+        Err(Error::InvalidIPAddress)
+    }
+}
+
+const EMPTY_PARTS: Parts = Parts {
+    display_start: 0,
+    display_end: 0,
+    local_start: 0,
+    separator: 0,
+    domain_start: 0,
+    domain_end: 0,
+};
+
+// ------------------------------------------------------------------------------------------------
+// Unit Tests
+// ------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{parse_address_ref, Options};
+    use proptest::prelude::*;
+
+    fn assert_matches_runtime(address: &str) {
+        let runtime = parse_address_ref(address, Options::default());
+        match check_static(address) {
+            Checked::Valid(parts) => {
+                let email = runtime.unwrap();
+                assert_eq!(EmailAddressRef::from_parts(address, parts), email);
+                assert_eq!(
+                    EmailAddressRef::from_parts(address, parts).as_str(),
+                    address
+                );
+                assert_eq!(parts, Parts::new(address), "{:?}", address);
+            }
+            Checked::Invalid(error) => {
+                assert_eq!(runtime.unwrap_err(), error, "{:?}", address)
+            }
+            Checked::Unsupported => (),
+        }
+    }
+
+    #[test]
+    fn test_matches_runtime() {
+        for address in &[
+            "simon@example.com",
+            "Simon Johnston <simon@example.com>",
+            " \u{3000}Simon\u{A0} <  simon@example.com \u{2003}",
+            "\u{3000}Simon\u{A0}<simon@example.com>\u{2003}",
+            "\"Doe, <John>\" <john@example.com>",
+            "<simon@example.com>",
+            "\"simon johnston\"@example.com",
+            "\"simon\\\"@\\\"johnston\"@example.com",
+            "\"\"@example.com",
+            "simon@[192.168.2.1]",
+            "simon@[IPv6:2001:db8::1]",
+            "simon@[ipv6:::ffff:192.168.2.1]",
+            "simon@[IPv6:2001:db8:0:0:0:0:0:1]",
+            "simon@[IPv6:2001:db8::0:0:0:0:0:1]",
+            "simon@[300.168.2.1]",
+            "simon@[x-tag:content]",
+            "用户@example.com",
+            "simon@bücher.example",
+            "simon@xn--bcher-kva.example",
+            "simon@例子.广告",
+            "simon",
+            "simon@",
+            "@example.com",
+            "si..mon@example.com",
+            "simon@example..com",
+            "simon@-example.com",
+            "simon@(comment)example.com",
+            "Simon <simon@example.com",
+        ] {
+            assert_matches_runtime(address);
+        }
+        assert_matches_runtime(&format!("{}@example.com", "s".repeat(65)));
+        assert_matches_runtime(&format!("simon@{}.com", "e".repeat(64)));
+        assert_matches_runtime(&format!("simon@{}com", "example.".repeat(32)));
+    }
+
+    #[test]
+    fn test_macro() {
+        const POSTMASTER: EmailAddressRef<'static> = crate::email!("postmaster@example.com");
+        static NO_REPLY: EmailAddressRef<'static> =
+            crate::email!("Example <no-reply@[IPv6:2001:db8::1]>");
+
+        assert_eq!(POSTMASTER.local_part(), "postmaster");
+        assert_eq!(NO_REPLY.display_part(), "Example");
+        assert_eq!(NO_REPLY.local_part(), "no-reply");
+        assert_eq!(NO_REPLY.domain(), "[IPv6:2001:db8::1]");
+    }
+
+    #[test]
+    fn test_unsupported() {
+        for address in &[
+            "simon@例子.广告",
+            "simon@über.example",
+            "simon@example.café",
+            "simon@xn--bcher-kva.example",
+            "Simon <simon@mail.XN--bcher-kva.example>",
+        ] {
+            assert!(
+                matches!(check_static(address), Checked::Unsupported),
+                "{:?}",
+                address
+            );
+            assert!(
+                parse_address_ref(address, Options::default()).is_ok(),
+                "{:?}",
+                address
+            );
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "A sub-domain is empty.")]
+    fn test_runtime_panic() {
+        let address: &'static str = Box::leak(String::from("simon@example..com").into_boxed_str());
+        let _ = EmailAddressRef::from_static(address);
+    }
+
+    proptest! {
+        #[test]
+        fn matches_runtime(address in r#"[ "\\a-z0-9.@<>\[\]():-]{0,24}"#) {
+            assert_matches_runtime(&address);
+        }
+
+        #[test]
+        fn matches_runtime_structured(
+            display in r#"( ?"?[A-Za-z ]{0,4}"? ?<)?"#,
+            local_part in r#"[a-z0-9.+"\\ ]{0,8}"#,
+            domain in r"[a-z0-9.-]{0,8}|\[[0-9.:IPv6]{0,20}\]",
+            close in ">?",
+        ) {
+            assert_matches_runtime(&format!("{}{}@{}{}", display, local_part, domain, close));
+        }
+    }
+}
@@ -0,0 +1,623 @@
+/*!
+Support for the individual productions of an address as separate, validated, types.
+
+From RFC 5322, §3.4. [Address Specification](https://tools.ietf.org/html/rfc5322#section-3.4):
+
+```ebnf
+mailbox         =   name-addr / addr-spec
+
+name-addr       =   [display-name] angle-addr
+
+angle-addr      =   [CFWS] "<" addr-spec ">" [CFWS] /
+                    obs-angle-addr
+
+addr-spec       =   local-part "@" domain
+```
+
+An [EmailAddress](crate::EmailAddress) may be either a `name-addr` or an `addr-spec`; an
+[AddrSpec] never has a display name, and a [Mailbox] may have one, so that an API can state in
+its types whether a display name is allowed. Each component is parsed with the default
+[Options](crate::Options), and comments are not retained.
+*/
+
+use crate::{
+    decode_display_name, encode_display_name, fold_domain, local_part_from_value, parse_domain,
+    parse_host, parse_local_part, strip_cfws, unquote_local_part, EmailAddress, Error, Host, AT,
+    DISPLAY_END, DISPLAY_START,
+};
+use std::borrow::Cow;
+use std::cmp::Ordering;
+use std::convert::TryFrom;
+use std::fmt::{Display, Formatter};
+use std::hash::{Hash, Hasher};
+use std::str::FromStr;
+
+// ------------------------------------------------------------------------------------------------
+// Public Types
+// ------------------------------------------------------------------------------------------------
+
+///
+/// A validated `local-part`, either a `dot-atom` or a `quoted-string`. Two values are equal if
+/// they are equal once any quoting is removed; the `local-part` is case-sensitive.
+///
+/// ```rust
+/// use email_address::*;
+/// use std::str::FromStr;
+///
+/// let local_part = LocalPart::from_str("\"simon\"").unwrap();
+///
+/// assert_eq!(local_part.value(), "simon");
+/// assert_eq!(local_part, LocalPart::from_str("simon").unwrap());
+/// assert_eq!(LocalPart::from_str("si mon"), Err(Error::InvalidCharacter));
+/// ```
+///
+#[derive(Debug, Clone)]
+pub struct LocalPart(String);
+
+///
+/// A validated `domain`, either a domain name or a domain literal. Two values are equal if they
+/// are equal ignoring case.
+///
+/// ```rust
+/// use email_address::*;
+/// use std::net::Ipv4Addr;
+/// use std::str::FromStr;
+///
+/// assert_eq!(
+///     Domain::from_str("Example.COM").unwrap(),
+///     Domain::from_str("example.com").unwrap()
+/// );
+/// assert_eq!(
+///     Domain::from_str("[192.168.2.1]").unwrap().host(),
+///     Host::Ipv4(Ipv4Addr::new(192, 168, 2, 1))
+/// );
+/// assert_eq!(Domain::from_str("example..com"), Err(Error::SubDomainEmpty));
+/// ```
+///
+#[derive(Debug, Clone)]
+pub struct Domain(String);
+
+///
+/// A validated `addr-spec`, a `local-part` and `domain` without any display name.
+///
+/// ```rust
+/// use email_address::*;
+/// use std::str::FromStr;
+///
+/// let addr_spec = AddrSpec::from_str("simon@example.com").unwrap();
+///
+/// assert_eq!(addr_spec.local_part().as_str(), "simon");
+/// assert_eq!(addr_spec.domain().as_str(), "example.com");
+/// assert_eq!(
+///     AddrSpec::from_str("Simon <simon@example.com>"),
+///     Err(Error::UnsupportedDisplayName)
+/// );
+/// ```
+///
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct AddrSpec {
+    local_part: LocalPart,
+    domain: Domain,
+}
+
+///
+/// A validated `mailbox`, an [AddrSpec] with an optional display name.
+///
+/// ```rust
+/// use email_address::*;
+/// use std::str::FromStr;
+///
+/// let mailbox = Mailbox::from_str("\"Johnston, Simon\" <simon@example.com>").unwrap();
+///
+/// assert_eq!(mailbox.display_part(), Some("\"Johnston, Simon\""));
+/// assert_eq!(mailbox.display_name().unwrap(), "Johnston, Simon");
+/// assert_eq!(mailbox.addr_spec().to_string(), "simon@example.com");
+///
+/// let mailbox = Mailbox::new(mailbox.addr_spec().clone()).with_display_name("Simon J.");
+///
+/// assert_eq!(mailbox.to_string(), "\"Simon J.\" <simon@example.com>");
+/// ```
+///
+/// Two mailboxes are equal if they have the same decoded display name, as returned by
+/// `display_name`, and equal `addr-spec`s; so `Simon <simon@example.com>` and
+/// `"Simon" <simon@example.com>` are equal, but `simon@example.com` is not equal to either.
+///
+#[derive(Debug, Clone)]
+pub struct Mailbox {
+    display_part: Option<String>,
+    addr_spec: AddrSpec,
+}
+
+// ------------------------------------------------------------------------------------------------
+// Implementations
+// ------------------------------------------------------------------------------------------------
+
+impl Display for LocalPart {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl PartialEq for LocalPart {
+    fn eq(&self, other: &Self) -> bool {
+        self.value() == other.value()
+    }
+}
+
+impl Eq for LocalPart {}
+
+impl Hash for LocalPart {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.value().hash(state);
+    }
+}
+
+impl PartialOrd for LocalPart {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for LocalPart {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.value().cmp(&other.value())
+    }
+}
+
+impl FromStr for LocalPart {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse_local_part(s, Default::default())?;
+        Ok(Self(s.to_string()))
+    }
+}
+
+impl From<LocalPart> for String {
+    fn from(local_part: LocalPart) -> Self {
+        local_part.0
+    }
+}
+
+impl AsRef<str> for LocalPart {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl LocalPart {
+    ///
+    /// Construct a `local-part` from its value, which is quoted only if it is not a valid
+    /// `dot-atom`, as by [EmailAddress::quote_local_part](crate::EmailAddress::quote_local_part).
+    ///
+    /// ```rust
+    /// use email_address::*;
+    ///
+    /// let local_part = LocalPart::from_value("joe blow").unwrap();
+    ///
+    /// assert_eq!(local_part.as_str(), "\"joe blow\"");
+    /// assert_eq!(local_part.value(), "joe blow");
+    /// assert_eq!(LocalPart::from_value("joe\u{7}"), Err(Error::InvalidCharacter));
+    /// ```
+    ///
+    pub fn from_value(value: &str) -> Result<Self, Error> {
+        Self::from_str(&local_part_from_value(value))
+    }
+
+    ///
+    /// Returns the `local-part` as written, including any quotes.
+    ///
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    ///
+    /// Returns the value of the `local-part`, without the quotes around a `quoted-string` and
+    /// with each `quoted-pair` replaced by the character it quotes.
+    ///
+    pub fn value(&self) -> Cow<'_, str> {
+        unquote_local_part(&self.0)
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl Display for Domain {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl PartialEq for Domain {
+    fn eq(&self, other: &Self) -> bool {
+        fold_domain(&self.0) == fold_domain(&other.0)
+    }
+}
+
+impl Eq for Domain {}
+
+impl Hash for Domain {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        fold_domain(&self.0).hash(state);
+    }
+}
+
+impl PartialOrd for Domain {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Domain {
+    fn cmp(&self, other: &Self) -> Ordering {
+        fold_domain(&self.0).cmp(&fold_domain(&other.0))
+    }
+}
+
+impl FromStr for Domain {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse_domain(s, Default::default())?;
+        Ok(Self(s.to_string()))
+    }
+}
+
+impl From<Domain> for String {
+    fn from(domain: Domain) -> Self {
+        domain.0
+    }
+}
+
+impl AsRef<str> for Domain {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Domain {
+    ///
+    /// Returns the domain as written.
+    ///
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    ///
+    /// Returns the domain parsed into a [Host], as
+    /// [EmailAddress::host](crate::EmailAddress::host).
+    ///
+    pub fn host(&self) -> Host<'_> {
+        parse_host(&self.0)
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl Display for AddrSpec {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}{}{}", self.local_part, AT, self.domain)
+    }
+}
+
+impl FromStr for AddrSpec {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::try_from(&EmailAddress::from_str(s)?)
+    }
+}
+
+impl TryFrom<&EmailAddress> for AddrSpec {
+    type Error = Error;
+
+    ///
+    /// Convert an address without a display name, returning `Error::UnsupportedDisplayName`
+    /// if it has one. Any comments are removed; an error is returned if a component is only
+    /// valid with options other than the defaults, such as a general address literal.
+    ///
+    fn try_from(email: &EmailAddress) -> Result<Self, Self::Error> {
+        if email.display_part().is_empty() {
+            addr_spec_of(email)
+        } else {
+            Error::UnsupportedDisplayName.into()
+        }
+    }
+}
+
+impl TryFrom<EmailAddress> for AddrSpec {
+    type Error = Error;
+
+    fn try_from(email: EmailAddress) -> Result<Self, Self::Error> {
+        Self::try_from(&email)
+    }
+}
+
+impl From<AddrSpec> for EmailAddress {
+    fn from(addr_spec: AddrSpec) -> Self {
+        EmailAddress::new_unchecked(addr_spec.to_string())
+    }
+}
+
+impl AddrSpec {
+    ///
+    /// Construct an `addr-spec` from its validated components.
+    ///
+    pub fn new(local_part: LocalPart, domain: Domain) -> Self {
+        Self { local_part, domain }
+    }
+
+    ///
+    /// Returns the `local-part`.
+    ///
+    pub fn local_part(&self) -> &LocalPart {
+        &self.local_part
+    }
+
+    ///
+    /// Returns the `domain`.
+    ///
+    pub fn domain(&self) -> &Domain {
+        &self.domain
+    }
+
+    ///
+    /// Returns the `local-part` and `domain`.
+    ///
+    pub fn into_parts(self) -> (LocalPart, Domain) {
+        (self.local_part, self.domain)
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl Display for Mailbox {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match &self.display_part {
+            None => write!(f, "{}", self.addr_spec),
+            Some(display_part) => write!(
+                f,
+                "{} {}{}{}",
+                display_part, DISPLAY_START, self.addr_spec, DISPLAY_END
+            ),
+        }
+    }
+}
+
+impl PartialEq for Mailbox {
+    fn eq(&self, other: &Self) -> bool {
+        self.display_name() == other.display_name() && self.addr_spec == other.addr_spec
+    }
+}
+
+impl Eq for Mailbox {}
+
+impl Hash for Mailbox {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.display_name().hash(state);
+        self.addr_spec.hash(state);
+    }
+}
+
+impl FromStr for Mailbox {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::try_from(&EmailAddress::from_str(s)?)
+    }
+}
+
+impl TryFrom<&EmailAddress> for Mailbox {
+    type Error = Error;
+
+    ///
+    /// Convert an address, with or without a display name. Any comments are removed; an
+    /// error is returned if a component is only valid with options other than the defaults,
+    /// such as a general address literal.
+    ///
+    fn try_from(email: &EmailAddress) -> Result<Self, Self::Error> {
+        let display_part = email.display_part();
+        Ok(Self {
+            display_part: if display_part.is_empty() {
+                None
+            } else {
+                Some(display_part.to_string())
+            },
+            addr_spec: addr_spec_of(email)?,
+        })
+    }
+}
+
+impl TryFrom<EmailAddress> for Mailbox {
+    type Error = Error;
+
+    fn try_from(email: EmailAddress) -> Result<Self, Self::Error> {
+        Self::try_from(&email)
+    }
+}
+
+impl From<Mailbox> for EmailAddress {
+    fn from(mailbox: Mailbox) -> Self {
+        EmailAddress::new_unchecked(mailbox.to_string())
+    }
+}
+
+impl From<AddrSpec> for Mailbox {
+    fn from(addr_spec: AddrSpec) -> Self {
+        Self::new(addr_spec)
+    }
+}
+
+impl Mailbox {
+    ///
+    /// Construct a `mailbox` without a display name.
+    ///
+    pub fn new(addr_spec: AddrSpec) -> Self {
+        Self {
+            display_part: None,
+            addr_spec,
+        }
+    }
+
+    ///
+    /// Returns a copy of this `mailbox` with the display name replaced by `display_name`, which
+    /// is written as an RFC 5322 `phrase` as by
+    /// [EmailAddress::to_display](crate::EmailAddress::to_display). If `display_name` is empty
+    /// the display name is removed.
+    ///
+    pub fn with_display_name(self, display_name: &str) -> Self {
+        let display_part = encode_display_name(display_name, false);
+        Self {
+            display_part: if display_part.is_empty() {
+                None
+            } else {
+                Some(display_part.into_owned())
+            },
+            ..self
+        }
+    }
+
+    ///
+    /// Returns the display name as written, if present.
+    ///
+    pub fn display_part(&self) -> Option<&str> {
+        self.display_part.as_deref()
+    }
+
+    ///
+    /// Returns the display name decoded from the raw `display_part`, as
+    /// [EmailAddress::display_name](crate::EmailAddress::display_name), if present.
+    ///
+    pub fn display_name(&self) -> Option<Cow<'_, str>> {
+        self.display_part.as_deref().map(decode_display_name)
+    }
+
+    ///
+    /// Returns the `addr-spec`.
+    ///
+    pub fn addr_spec(&self) -> &AddrSpec {
+        &self.addr_spec
+    }
+
+    ///
+    /// Returns the `addr-spec`, discarding any display name.
+    ///
+    pub fn into_addr_spec(self) -> AddrSpec {
+        self.addr_spec
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Functions
+// ------------------------------------------------------------------------------------------------
+
+fn addr_spec_of(email: &EmailAddress) -> Result<AddrSpec, Error> {
+    let (local_part, domain) = (email.local_part(), email.domain());
+    Ok(AddrSpec {
+        local_part: LocalPart::from_str(&strip_cfws(local_part)?)?,
+        domain: Domain::from_str(&strip_cfws(domain)?)?,
+    })
+}
+
+// ------------------------------------------------------------------------------------------------
+// Unit Tests
+// ------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Options;
+    use std::collections::hash_map::DefaultHasher;
+
+    #[test]
+    fn test_components() {
+        assert!(LocalPart::from_str("simon.johnston").is_ok());
+        assert!(LocalPart::from_str("\"simon johnston\"").is_ok());
+        assert_eq!(LocalPart::from_str(""), Err(Error::LocalPartEmpty));
+        assert_eq!(
+            LocalPart::from_str("simon(work)"),
+            Err(Error::InvalidCharacter)
+        );
+        assert_ne!(
+            LocalPart::from_str("Simon").unwrap(),
+            LocalPart::from_str("simon").unwrap()
+        );
+
+        assert!(Domain::from_str("[IPv6:2001:db8::1]").is_ok());
+        assert_eq!(Domain::from_str(""), Err(Error::DomainEmpty));
+        assert_eq!(
+            Domain::from_str("[x-tag:content]"),
+            Err(Error::InvalidIPAddress)
+        );
+
+        for part in &["simon", "\"si mon\"", "si mon", "", "si..mon"] {
+            assert_eq!(
+                LocalPart::from_str(part).is_ok(),
+                EmailAddress::is_valid_local_part(part)
+            );
+        }
+        for part in &["example.com", "[192.168.2.1]", "-example.com", ""] {
+            assert_eq!(
+                Domain::from_str(part).is_ok(),
+                EmailAddress::is_valid_domain(part)
+            );
+        }
+    }
+
+    #[test]
+    fn test_mailbox_equality() {
+        let hash_of = |mailbox: &Mailbox| {
+            let mut hasher = DefaultHasher::new();
+            mailbox.hash(&mut hasher);
+            hasher.finish()
+        };
+        let plain = Mailbox::from_str("A <a@x.com>").unwrap();
+        let quoted = Mailbox::from_str("\"A\" <a@X.com>").unwrap();
+
+        assert_eq!(plain, quoted);
+        assert_eq!(hash_of(&plain), hash_of(&quoted));
+        assert_ne!(plain, Mailbox::from_str("B <a@x.com>").unwrap());
+        assert_ne!(plain, Mailbox::from_str("a@x.com").unwrap());
+    }
+
+    #[test]
+    fn test_conversions() {
+        let addr_spec = AddrSpec::new(
+            LocalPart::from_str("simon").unwrap(),
+            Domain::from_str("example.com").unwrap(),
+        );
+        let email = EmailAddress::from(addr_spec.clone());
+        assert_eq!(email.as_str(), "simon@example.com");
+        assert_eq!(AddrSpec::try_from(email).unwrap(), addr_spec);
+
+        let mailbox = Mailbox::from(addr_spec).with_display_name("Simon");
+        let email = EmailAddress::from(mailbox.clone());
+        assert_eq!(email.as_str(), "Simon <simon@example.com>");
+        assert_eq!(Mailbox::try_from(&email).unwrap(), mailbox);
+        assert_eq!(
+            AddrSpec::try_from(&email),
+            Err(Error::UnsupportedDisplayName)
+        );
+        assert_eq!(
+            mailbox.with_display_name("").to_string(),
+            "simon@example.com"
+        );
+
+        let email = EmailAddress::parse_with_options(
+            "Simon <simon(work)@example.com (home)>",
+            Options::default().with_comments(),
+        )
+        .unwrap();
+        assert_eq!(
+            Mailbox::try_from(email).unwrap().to_string(),
+            "Simon <simon@example.com>"
+        );
+
+        let email = EmailAddress::parse_with_options(
+            "simon@[x-tag:content]",
+            Options::default().with_general_literal(),
+        )
+        .unwrap();
+        assert_eq!(AddrSpec::try_from(email), Err(Error::InvalidIPAddress));
+    }
+}
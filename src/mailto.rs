@@ -0,0 +1,672 @@
+/*!
+Support for the `mailto` URI scheme.
+
+From RFC 6068, §2. [Syntax of a 'mailto' URI](https://tools.ietf.org/html/rfc6068#section-2):
+
+```ebnf
+mailtoURI    = "mailto:" [ to ] [ hfields ]
+to           = addr-spec *("," addr-spec )
+hfields      = "?" hfield *( "&" hfield )
+hfield       = hfname "=" hfvalue
+hfname       = *qchar
+hfvalue      = *qchar
+addr-spec    = local-part "@" domain
+local-part   = dot-atom-text / quoted-string
+domain       = dot-atom-text / "[" *dtext-no-obs "]"
+dtext-no-obs = %d33-90 / ; Printable US-ASCII
+               %d94-126  ; characters not including
+                         ; "[", "]", or "\"
+qchar        = unreserved / pct-encoded / some-delims
+some-delims  = "!" / "$" / "'" / "(" / ")" / "*"
+               / "+" / "," / ";" / ":" / "@"
+```
+
+Where `unreserved` and `pct-encoded` are defined in RFC 3986, and any character not allowed by
+the rules above, including all non-ASCII characters, is percent-encoded as UTF-8.
+*/
+
+use crate::{parse_address, EmailAddress, Error, Options, DQUOTE, ESC};
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+// ------------------------------------------------------------------------------------------------
+// Public Types
+// ------------------------------------------------------------------------------------------------
+
+///
+/// Type representing a `mailto` URI, one or more recipient addresses along with optional header
+/// fields such as `subject` and `body`. The `Display` implementation writes the URI with all
+/// components percent-encoded as required by RFC 6068.
+///
+/// ```rust
+/// use email_address::*;
+/// use std::str::FromStr;
+///
+/// let uri = MailtoUri::new(EmailAddress::from_str("simon@example.com").unwrap())
+///     .with_cc(EmailAddress::from_str("jane@example.com").unwrap())
+///     .with_subject("Hello & welcome")
+///     .with_body("Line 1\nLine 2");
+///
+/// assert_eq!(
+///     uri.to_string(),
+///     "mailto:simon@example.com?cc=jane@example.com&subject=Hello%20%26%20welcome&body=Line%201%0D%0ALine%202"
+/// );
+/// ```
+///
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct MailtoUri {
+    to: Vec<EmailAddress>,
+    cc: Vec<EmailAddress>,
+    bcc: Vec<EmailAddress>,
+    subject: Option<String>,
+    body: Option<String>,
+    headers: Vec<(String, String)>,
+}
+
+// ------------------------------------------------------------------------------------------------
+// Implementations
+// ------------------------------------------------------------------------------------------------
+
+const MAILTO_URI_PREFIX: &str = "mailto:";
+
+const HFIELDS_START: char = '?';
+const HFIELD_SEP: char = '&';
+const HFNAME_SEP: char = '=';
+const ADDRESS_SEP: char = ',';
+const SEMICOLON: char = ';';
+
+const PCT_ENCODED: char = '%';
+
+const HFNAME_TO: &str = "to";
+const HFNAME_CC: &str = "cc";
+const HFNAME_BCC: &str = "bcc";
+const HFNAME_SUBJECT: &str = "subject";
+const HFNAME_BODY: &str = "body";
+
+// ------------------------------------------------------------------------------------------------
+
+impl Display for MailtoUri {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}{}", MAILTO_URI_PREFIX, encode_addresses(&self.to))?;
+        let mut hfields = self.hfields().into_iter();
+        if let Some((name, value)) = hfields.next() {
+            write!(f, "{}{}{}{}", HFIELDS_START, name, HFNAME_SEP, value)?;
+        }
+        for (name, value) in hfields {
+            write!(f, "{}{}{}{}", HFIELD_SEP, name, HFNAME_SEP, value)?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for MailtoUri {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse_uri(s, Default::default())
+    }
+}
+
+impl From<EmailAddress> for MailtoUri {
+    fn from(address: EmailAddress) -> Self {
+        Self::new(address)
+    }
+}
+
+impl MailtoUri {
+    ///
+    /// Parses a `mailto` URI with custom [Options], which are applied to each address. All
+    /// components are percent-decoded; the addresses in the `to` component, and any `to`, `cc`,
+    /// or `bcc` header fields, are parsed as comma-separated `addr-spec`s, so display names,
+    /// comments, and groups are rejected. Other header fields are retained as name and value
+    /// pairs.
+    ///
+    /// ```rust
+    /// use email_address::*;
+    ///
+    /// let uri = MailtoUri::parse_with_options(
+    ///     "mailto:a@example.com,b@example.com?subject=caf%C3%A9&cc=c@example.com",
+    ///     Options::default(),
+    /// ).unwrap();
+    ///
+    /// assert_eq!(uri.to().len(), 2);
+    /// assert_eq!(uri.cc()[0].as_str(), "c@example.com");
+    /// assert_eq!(uri.subject(), Some("café"));
+    /// ```
+    ///
+    pub fn parse_with_options(uri: &str, options: Options) -> Result<Self, Error> {
+        parse_uri(uri, options)
+    }
+
+    ///
+    /// Create a new URI with a single recipient address.
+    ///
+    pub fn new(to: EmailAddress) -> Self {
+        Self {
+            to: vec![to],
+            ..Default::default()
+        }
+    }
+
+    /// Add a recipient address to the `to` component of the URI.
+    pub fn with_to(mut self, to: EmailAddress) -> Self {
+        self.to.push(to);
+        self
+    }
+
+    /// Add an address to the `cc` header field.
+    pub fn with_cc(mut self, cc: EmailAddress) -> Self {
+        self.cc.push(cc);
+        self
+    }
+
+    /// Add an address to the `bcc` header field.
+    pub fn with_bcc(mut self, bcc: EmailAddress) -> Self {
+        self.bcc.push(bcc);
+        self
+    }
+
+    /// Set the value of the `subject` header field.
+    pub fn with_subject<S>(self, subject: S) -> Self
+    where
+        S: Into<String>,
+    {
+        Self {
+            subject: Some(subject.into()),
+            ..self
+        }
+    }
+
+    /// Set the value of the `body` pseudo header field, line breaks are encoded as CRLF.
+    pub fn with_body<S>(self, body: S) -> Self
+    where
+        S: Into<String>,
+    {
+        Self {
+            body: Some(body.into()),
+            ..self
+        }
+    }
+
+    /// Add any other header field, such as `in-reply-to`.
+    pub fn with_header<N, V>(mut self, name: N, value: V) -> Self
+    where
+        N: Into<String>,
+        V: Into<String>,
+    {
+        self.headers.push((name.into(), value.into()));
+        self
+    }
+
+    /// Returns the recipient addresses in the `to` component of the URI.
+    pub fn to(&self) -> &[EmailAddress] {
+        &self.to
+    }
+
+    /// Returns the addresses in the `cc` header field.
+    pub fn cc(&self) -> &[EmailAddress] {
+        &self.cc
+    }
+
+    /// Returns the addresses in the `bcc` header field.
+    pub fn bcc(&self) -> &[EmailAddress] {
+        &self.bcc
+    }
+
+    /// Returns the value of the `subject` header field, if present.
+    pub fn subject(&self) -> Option<&str> {
+        self.subject.as_deref()
+    }
+
+    /// Returns the value of the `body` pseudo header field, if present.
+    pub fn body(&self) -> Option<&str> {
+        self.body.as_deref()
+    }
+
+    /// Returns any other header fields, as name and value pairs.
+    pub fn headers(&self) -> &[(String, String)] {
+        &self.headers
+    }
+
+    ///
+    /// All the header fields, names and values, with their percent-encoding applied.
+    ///
+    fn hfields(&self) -> Vec<(String, String)> {
+        let mut hfields = Vec::default();
+        if !self.cc.is_empty() {
+            hfields.push((HFNAME_CC.to_string(), encode_addresses(&self.cc)));
+        }
+        if !self.bcc.is_empty() {
+            hfields.push((HFNAME_BCC.to_string(), encode_addresses(&self.bcc)));
+        }
+        if let Some(subject) = &self.subject {
+            hfields.push((HFNAME_SUBJECT.to_string(), encode_hfvalue(subject)));
+        }
+        if let Some(body) = &self.body {
+            hfields.push((
+                HFNAME_BODY.to_string(),
+                encode_hfvalue(&normalize_line_breaks(body)),
+            ));
+        }
+        for (name, value) in &self.headers {
+            hfields.push((encode_hfvalue(name), encode_hfvalue(value)));
+        }
+        hfields
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Functions
+// ------------------------------------------------------------------------------------------------
+
+fn parse_uri(uri: &str, options: Options) -> Result<MailtoUri, Error> {
+    let rest = match uri.get(..MAILTO_URI_PREFIX.len()) {
+        Some(scheme) if scheme.eq_ignore_ascii_case(MAILTO_URI_PREFIX) => {
+            &uri[MAILTO_URI_PREFIX.len()..]
+        }
+        _ => return Error::InvalidUri.into(),
+    };
+    let (to, hfields) = match rest.split_once(HFIELDS_START) {
+        None => (rest, None),
+        Some((to, hfields)) => (to, Some(hfields)),
+    };
+
+    let mut result = MailtoUri {
+        to: parse_addresses(to, options)?,
+        ..Default::default()
+    };
+    for hfield in hfields
+        .into_iter()
+        .flat_map(|hfields| hfields.split(HFIELD_SEP))
+    {
+        if hfield.is_empty() {
+            continue;
+        }
+        let (name, value) = hfield.split_once(HFNAME_SEP).ok_or(Error::InvalidUri)?;
+        let name = decode(name)?;
+        if name.eq_ignore_ascii_case(HFNAME_TO) {
+            result.to.append(&mut parse_addresses(value, options)?);
+        } else if name.eq_ignore_ascii_case(HFNAME_CC) {
+            result.cc.append(&mut parse_addresses(value, options)?);
+        } else if name.eq_ignore_ascii_case(HFNAME_BCC) {
+            result.bcc.append(&mut parse_addresses(value, options)?);
+        } else if name.eq_ignore_ascii_case(HFNAME_SUBJECT) {
+            result.subject = Some(decode(value)?);
+        } else if name.eq_ignore_ascii_case(HFNAME_BODY) {
+            result.body = Some(decode(value)?);
+        } else {
+            result.headers.push((name, decode(value)?));
+        }
+    }
+    Ok(result)
+}
+
+///
+/// Parse a comma-separated list of `addr-spec`s; display names, comments, and groups are not
+/// allowed by RFC 6068 and are rejected.
+///
+fn parse_addresses(text: &str, options: Options) -> Result<Vec<EmailAddress>, Error> {
+    let text = decode(text)?;
+    if text.is_empty() {
+        return Ok(Vec::default());
+    }
+    let options = options.without_display_text().without_comments();
+    split_addresses(&text)
+        .into_iter()
+        .map(|address| {
+            if address.is_empty() {
+                Error::InvalidUri.into()
+            } else {
+                parse_address(address, options)
+            }
+        })
+        .collect()
+}
+
+///
+/// Split `text` at each `,` that is not within a quoted string.
+///
+fn split_addresses(text: &str) -> Vec<&str> {
+    let mut addresses = Vec::default();
+    let mut in_quotes = false;
+    let mut start = 0;
+    let mut char_iter = text.char_indices();
+    while let Some((index, c)) = char_iter.next() {
+        match c {
+            ESC if in_quotes => {
+                let _ = char_iter.next();
+            }
+            DQUOTE => in_quotes = !in_quotes,
+            ADDRESS_SEP if !in_quotes => {
+                addresses.push(&text[start..index]);
+                start = index + 1;
+            }
+            _ => (),
+        }
+    }
+    addresses.push(&text[start..]);
+    addresses
+}
+
+///
+/// Decode all percent-encoded octets in `text`, which must result in a valid UTF-8 string.
+///
+fn decode(text: &str) -> Result<String, Error> {
+    if !text.contains(PCT_ENCODED) {
+        return Ok(text.to_string());
+    }
+    let mut bytes = Vec::with_capacity(text.len());
+    let mut byte_iter = text.bytes();
+    while let Some(b) = byte_iter.next() {
+        if b == PCT_ENCODED as u8 {
+            let high = byte_iter.next().and_then(hex_value);
+            let low = byte_iter.next().and_then(hex_value);
+            match (high, low) {
+                (Some(high), Some(low)) => bytes.push(high << 4 | low),
+                _ => return Error::InvalidUri.into(),
+            }
+        } else {
+            bytes.push(b);
+        }
+    }
+    String::from_utf8(bytes).map_err(|_| Error::InvalidUri)
+}
+
+fn hex_value(b: u8) -> Option<u8> {
+    char::from(b).to_digit(16).map(|d| d as u8)
+}
+
+fn encode_addresses(addresses: &[EmailAddress]) -> String {
+    addresses
+        .iter()
+        .map(|address| encode(address.strip_comments().email(), is_to_char))
+        .collect::<Vec<_>>()
+        .join(&ADDRESS_SEP.to_string())
+}
+
+fn encode_hfvalue(value: &str) -> String {
+    encode(value, is_hfield_char)
+}
+
+///
+/// Percent-encode, as UTF-8, every character in `text` for which `is_allowed` is false.
+///
+fn encode(text: &str, is_allowed: fn(char) -> bool) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut buffer = [0_u8; 4];
+    for c in text.chars() {
+        if is_allowed(c) {
+            result.push(c);
+        } else {
+            for b in c.encode_utf8(&mut buffer).bytes() {
+                result.push_str(&format!("%{:02X}", b));
+            }
+        }
+    }
+    result
+}
+
+///
+/// RFC 6068 requires that line breaks in the body are encoded as `%0D%0A`.
+///
+fn normalize_line_breaks(text: &str) -> String {
+    text.replace("\r\n", "\n").replace('\n', "\r\n")
+}
+
+///
+/// The RFC 3986 `unreserved` production.
+///
+fn is_unreserved(c: char) -> bool {
+    c.is_ascii_alphanumeric() || matches!(c, '-' | '.' | '_' | '~')
+}
+
+///
+/// The RFC 6068 `some-delims` production.
+///
+fn is_some_delim(c: char) -> bool {
+    matches!(
+        c,
+        '!' | '$' | '\'' | '(' | ')' | '*' | '+' | ',' | ';' | ':' | '@'
+    )
+}
+
+///
+/// Characters allowed unencoded in an address in the `to` component, or in an address list
+/// header field; `,` is encoded as it separates addresses, and RFC 6068 requires that `;` is
+/// encoded in an `addr-spec`.
+///
+fn is_to_char(c: char) -> bool {
+    c != ADDRESS_SEP && c != SEMICOLON && (is_unreserved(c) || is_some_delim(c))
+}
+
+///
+/// Characters allowed unencoded in a header field name or value, the `qchar` production.
+///
+fn is_hfield_char(c: char) -> bool {
+    is_unreserved(c) || is_some_delim(c)
+}
+
+// ------------------------------------------------------------------------------------------------
+// Unit Tests
+// ------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    fn email(address: &str) -> EmailAddress {
+        EmailAddress::from_str(address).unwrap()
+    }
+
+    #[test]
+    fn test_simple_uri() {
+        assert_eq!(
+            MailtoUri::new(email("chris@example.com")).to_string(),
+            "mailto:chris@example.com"
+        );
+    }
+
+    #[test]
+    fn test_multiple_to() {
+        assert_eq!(
+            MailtoUri::new(email("Simon <simon@example.com>"))
+                .with_to(email("\"a,b\"@example.com"))
+                .to_string(),
+            "mailto:simon@example.com,%22a%2Cb%22@example.com"
+        );
+    }
+
+    #[test]
+    fn test_addr_spec_delimiters_encoded() {
+        let uri = MailtoUri::new(email("\"a;b\"@example.com"))
+            .with_cc(email("\"c&d=e\"@example.com"))
+            .with_subject("a;b");
+
+        assert_eq!(
+            uri.to_string(),
+            "mailto:%22a%3Bb%22@example.com?cc=%22c%26d%3De%22@example.com&subject=a;b"
+        );
+        assert_eq!(MailtoUri::from_str(&uri.to_string()).unwrap(), uri);
+    }
+
+    #[test]
+    fn test_rfc_6068_examples() {
+        // RFC 6068, §6.1. Basic Examples
+        assert_eq!(
+            MailtoUri::new(email("infobot@example.com"))
+                .with_subject("current-issue")
+                .to_string(),
+            "mailto:infobot@example.com?subject=current-issue"
+        );
+        assert_eq!(
+            MailtoUri::new(email("infobot@example.com"))
+                .with_body("send current-issue\nsend index")
+                .to_string(),
+            "mailto:infobot@example.com?body=send%20current-issue%0D%0Asend%20index"
+        );
+        assert_eq!(
+            MailtoUri::new(email("list@example.org"))
+                .with_header("In-Reply-To", "<3469A91.D10AF4C@example.com>")
+                .to_string(),
+            "mailto:list@example.org?In-Reply-To=%3C3469A91.D10AF4C@example.com%3E"
+        );
+        // RFC 6068, §6.2. Examples of Complicated Email Addresses
+        assert_eq!(
+            MailtoUri::new(email("\"not@me\"@example.org")).to_string(),
+            "mailto:%22not@me%22@example.org"
+        );
+        assert_eq!(
+            MailtoUri::new(email("\"oh\\\\no\"@example.org")).to_string(),
+            "mailto:%22oh%5C%5Cno%22@example.org"
+        );
+        assert_eq!(
+            MailtoUri::new(email("user@example.org"))
+                .with_subject("caf\u{e9}")
+                .to_string(),
+            "mailto:user@example.org?subject=caf%C3%A9"
+        );
+    }
+
+    #[test]
+    fn test_parse_simple_uri() {
+        let uri = MailtoUri::from_str("MailTo:chris@example.com").unwrap();
+
+        assert_eq!(uri.to(), &[email("chris@example.com")]);
+        assert!(uri.cc().is_empty());
+        assert_eq!(uri.subject(), None);
+    }
+
+    #[test]
+    fn test_parse_rfc_6068_examples() {
+        let uri = MailtoUri::from_str(
+            "mailto:infobot@example.com?body=send%20current-issue%0D%0Asend%20index",
+        )
+        .unwrap();
+        assert_eq!(uri.body(), Some("send current-issue\r\nsend index"));
+
+        let uri = MailtoUri::from_str("mailto:?to=joe@example.com&cc=bob@example.com&body=hello")
+            .unwrap();
+        assert_eq!(uri.to(), &[email("joe@example.com")]);
+        assert_eq!(uri.cc(), &[email("bob@example.com")]);
+        assert_eq!(uri.body(), Some("hello"));
+
+        let uri =
+            MailtoUri::from_str("mailto:joe@example.com?cc=bob@example.com&bcc=carol@example.com")
+                .unwrap();
+        assert_eq!(uri.bcc(), &[email("carol@example.com")]);
+
+        let uri = MailtoUri::from_str(
+            "mailto:list@example.org?In-Reply-To=%3C3469A91.D10AF4C@example.com%3E",
+        )
+        .unwrap();
+        assert_eq!(
+            uri.headers(),
+            &[(
+                "In-Reply-To".to_string(),
+                "<3469A91.D10AF4C@example.com>".to_string()
+            )]
+        );
+
+        let uri = MailtoUri::from_str("mailto:%22not@me%22@example.org").unwrap();
+        assert_eq!(uri.to(), &[email("\"not@me\"@example.org")]);
+
+        let uri = MailtoUri::from_str("mailto:user@%E7%94%A8%E6%88%B7.example").unwrap();
+        assert_eq!(uri.to(), &[email("user@用户.example")]);
+    }
+
+    #[test]
+    fn test_parse_multiple_to() {
+        let uri =
+            MailtoUri::from_str("mailto:a@example.com,%22b,c%22@example.com?to=d@example.com")
+                .unwrap();
+
+        assert_eq!(
+            uri.to(),
+            &[
+                email("a@example.com"),
+                email("\"b,c\"@example.com"),
+                email("d@example.com")
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(
+            MailtoUri::from_str("http://example.com"),
+            Err(Error::InvalidUri)
+        );
+        assert_eq!(
+            MailtoUri::from_str("mailto:a@example.com?subject=%2"),
+            Err(Error::InvalidUri)
+        );
+        assert_eq!(
+            MailtoUri::from_str("mailto:a@example.com?subject=%FF"),
+            Err(Error::InvalidUri)
+        );
+        assert_eq!(
+            MailtoUri::from_str("mailto:a@example.com?subject"),
+            Err(Error::InvalidUri)
+        );
+        assert_eq!(
+            MailtoUri::from_str("mailto:a@example.com,b.example.com"),
+            Err(Error::MissingSeparator)
+        );
+        assert_eq!(
+            MailtoUri::from_str("mailto:a@example.com,,b@example.com"),
+            Err(Error::InvalidUri)
+        );
+    }
+
+    #[test]
+    fn test_parse_rejects_non_addr_spec() {
+        assert_eq!(
+            MailtoUri::from_str("mailto:Simon%20%3Csimon@example.com%3E"),
+            Err(Error::UnsupportedDisplayName)
+        );
+        assert_eq!(
+            MailtoUri::from_str("mailto:?cc=Simon%20%3Csimon@example.com%3E"),
+            Err(Error::UnsupportedDisplayName)
+        );
+        assert!(MailtoUri::from_str("mailto:team:%20a@example.com;").is_err());
+        assert!(MailtoUri::from_str("mailto:simon(work)@example.com").is_err());
+        assert!(MailtoUri::parse_with_options(
+            "mailto:simon(work)@example.com",
+            Options::default().with_comments()
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_parse_roundtrip() {
+        let uri = MailtoUri::new(email("\"a,b\"@пример.рф"))
+            .with_to(email("c@example.com"))
+            .with_cc(email("b@example.com"))
+            .with_bcc(email("d@example.com"))
+            .with_subject("100% = a? & b")
+            .with_body("line 1\r\nline 2")
+            .with_header("x-tag", "a&b=c");
+
+        assert_eq!(MailtoUri::from_str(&uri.to_string()).unwrap(), uri);
+    }
+
+    #[test]
+    fn test_all_hfields() {
+        let uri = MailtoUri::new(email("a@example.com"))
+            .with_cc(email("b@example.com"))
+            .with_cc(email("c@example.com"))
+            .with_bcc(email("d@example.com"))
+            .with_subject("100% = a?")
+            .with_body("é")
+            .with_header("x-tag", "a&b");
+
+        assert_eq!(
+            uri.to_string(),
+            "mailto:a@example.com?cc=b@example.com,c@example.com&bcc=d@example.com\
+             &subject=100%25%20%3D%20a%3F&body=%C3%A9&x-tag=a%26b"
+        );
+        assert_eq!(uri.cc().len(), 2);
+        assert_eq!(uri.subject(), Some("100% = a?"));
+    }
+}
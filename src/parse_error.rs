@@ -0,0 +1,546 @@
+/*!
+Support for locating a parse failure within the original address text.
+
+When [EmailAddress::parse_detailed](crate::EmailAddress::parse_detailed) fails it returns a
+[ParseError], which wraps the same [Error] returned by `from_str` with the byte range of the text
+in error, the offending character where there is one, and the component of the address in which
+the failure was found.
+
+```text
+"Simon Johnston <simon@exa mple.com>"
+ ^------------^ DisplayName
+                 ^---^ LocalPart
+                       ^---------^ Domain
+                       ^-------^ DomainLabel(0)
+                                ^-^ DomainLabel(1)
+```
+*/
+
+use crate::idna::domain_to_ascii;
+use crate::{
+    is_atext, is_dtext_char, is_qtext_char, is_vchar, is_wsp, parse_sub_domain, strip_cfws, Error,
+    Options, DISPLAY_START, DOT, DQUOTE, ESC, LBRACKET, RBRACKET,
+};
+use std::borrow::Cow;
+use std::fmt::{Display, Formatter};
+use std::ops::Range;
+
+// ------------------------------------------------------------------------------------------------
+// Public Types
+// ------------------------------------------------------------------------------------------------
+
+///
+/// The component of an address in which a [ParseError] was found.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Component {
+    /// The address as a whole, for example when the `@` separator is missing.
+    Address,
+    /// The display name preceding an address in angle brackets.
+    DisplayName,
+    /// The `local-part` of the address.
+    LocalPart,
+    /// The `domain` of the address as a whole, including any `domain-literal`.
+    Domain,
+    /// A single label of the `domain`, identified by its zero-based index.
+    DomainLabel(usize),
+}
+
+///
+/// An [Error] along with its location in the text that was parsed. The `span` is a byte range
+/// into the original text, and `found` is the offending character when the error concerns a
+/// single character.
+///
+/// ```rust
+/// use email_address::*;
+///
+/// let error = EmailAddress::parse_detailed("simon@exa mple.com", Options::default())
+///     .unwrap_err();
+///
+/// assert_eq!(error.kind, Error::InvalidCharacter);
+/// assert_eq!(error.span, 9..10);
+/// assert_eq!(error.found, Some(' '));
+/// assert_eq!(error.component, Component::DomainLabel(0));
+///
+/// assert_eq!(Error::from(error), Error::InvalidCharacter);
+/// ```
+///
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseError {
+    /// The kind of error, as returned by `from_str`.
+    pub kind: Error,
+    /// The byte range of the text in error.
+    pub span: Range<usize>,
+    /// The offending character, if the error concerns a single character.
+    pub found: Option<char>,
+    /// The component of the address in which the error was found.
+    pub component: Component,
+}
+
+// ------------------------------------------------------------------------------------------------
+// Implementations
+// ------------------------------------------------------------------------------------------------
+
+impl Display for Component {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Component::Address => write!(f, "address"),
+            Component::DisplayName => write!(f, "display name"),
+            Component::LocalPart => write!(f, "local part"),
+            Component::Domain => write!(f, "domain"),
+            Component::DomainLabel(index) => write!(f, "domain label {}", index),
+        }
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self.found {
+            Some(c) => write!(
+                f,
+                "{} Found {:?} at byte {} in the {}.",
+                self.kind, c, self.span.start, self.component
+            ),
+            None => write!(
+                f,
+                "{} Found at bytes {}..{} in the {}.",
+                self.kind, self.span.start, self.span.end, self.component
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.kind)
+    }
+}
+
+impl From<ParseError> for Error {
+    fn from(error: ParseError) -> Self {
+        error.kind
+    }
+}
+
+impl ParseError {
+    pub(crate) fn new(
+        kind: Error,
+        span: Range<usize>,
+        found: Option<char>,
+        component: Component,
+    ) -> Self {
+        Self {
+            kind,
+            span,
+            found,
+            component,
+        }
+    }
+
+    pub(crate) fn at_char(kind: Error, start: usize, c: char, component: Component) -> Self {
+        Self::new(kind, start..start + c.len_utf8(), Some(c), component)
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Crate Functions
+// ------------------------------------------------------------------------------------------------
+
+///
+/// Returns the byte range of `part` within `text`, `part` must be a sub-slice of `text`.
+///
+pub(crate) fn span_of(text: &str, part: &str) -> Range<usize> {
+    let start = part.as_ptr() as usize - text.as_ptr() as usize;
+    debug_assert!(start + part.len() <= text.len());
+    start..start + part.len()
+}
+
+///
+/// Locate the error `kind`, reported by the parser against `subject` in the `component`, within
+/// `text`; `subject` must be a sub-slice of `text`.
+///
+pub(crate) fn locate(
+    text: &str,
+    subject: &str,
+    component: Component,
+    options: Options,
+    kind: Error,
+) -> ParseError {
+    match component {
+        Component::LocalPart => locate_in_local_part(text, subject, options, kind),
+        Component::Domain => locate_in_domain(text, subject, options, kind),
+        Component::DomainLabel(index) => locate_in_label(text, subject, index, kind),
+        Component::DisplayName if kind == Error::MissingDisplayName => {
+            ParseError::at_char(kind, span_of(text, subject).start, DISPLAY_START, component)
+        }
+        _ => ParseError::new(kind, span_of(text, subject), None, component),
+    }
+}
+
+///
+/// Locate the error `kind`, returned when parsing `part` as a `local-part`, within `text`.
+///
+pub(crate) fn locate_in_local_part(
+    text: &str,
+    part: &str,
+    options: Options,
+    kind: Error,
+) -> ParseError {
+    let part = match uncommented(part, options) {
+        Some(part) => part,
+        None => return ParseError::new(kind, span_of(text, part), None, Component::LocalPart),
+    };
+    let span = span_of(text, part);
+    let found = match kind {
+        Error::InvalidCharacter => find_local_part_char(part),
+        Error::RequiresSmtpUtf8 => find_non_ascii(part),
+        _ => None,
+    };
+    match found {
+        Some((index, c)) => ParseError::at_char(kind, span.start + index, c, Component::LocalPart),
+        None => ParseError::new(kind, span, None, Component::LocalPart),
+    }
+}
+
+///
+/// Locate the error `kind`, returned when parsing `part` as a `domain`, within `text`.
+///
+pub(crate) fn locate_in_domain(
+    text: &str,
+    part: &str,
+    options: Options,
+    kind: Error,
+) -> ParseError {
+    let part = match uncommented(part, options) {
+        Some(part) => part,
+        None => return ParseError::new(kind, span_of(text, part), None, Component::Domain),
+    };
+    let span = span_of(text, part);
+    let whole_domain = |found: Option<(usize, char)>| match found {
+        Some((index, c)) => {
+            ParseError::at_char(kind.clone(), span.start + index, c, Component::Domain)
+        }
+        None => ParseError::new(kind.clone(), span.clone(), None, Component::Domain),
+    };
+
+    if let Error::DomainEmpty | Error::DomainTooLong | Error::DomainTooFew = kind {
+        whole_domain(None)
+    } else if part.starts_with(LBRACKET) && part.ends_with(RBRACKET) {
+        whole_domain(match kind {
+            Error::InvalidCharacter => part[1..part.len() - 1]
+                .char_indices()
+                .find(|(_, c)| !is_dtext_char(*c))
+                .map(|(index, c)| (index + 1, c)),
+            Error::RequiresSmtpUtf8 => find_non_ascii(part),
+            _ => None,
+        })
+    } else {
+        for (index, label) in part.split(DOT).enumerate() {
+            if label_error(label, options).as_ref() == Some(&kind) {
+                return locate_in_label(text, label, index, kind);
+            }
+        }
+        whole_domain(None)
+    }
+}
+
+///
+/// Locate the error `kind`, returned when parsing `label` as the `index`th label of a `domain`,
+/// within `text`.
+///
+pub(crate) fn locate_in_label(text: &str, label: &str, index: usize, kind: Error) -> ParseError {
+    let span = span_of(text, label);
+    let found = match kind {
+        Error::InvalidCharacter => find_label_char(label),
+        _ => None,
+    };
+    match found {
+        Some((offset, c)) => {
+            ParseError::at_char(kind, span.start + offset, c, Component::DomainLabel(index))
+        }
+        None => ParseError::new(kind, span, None, Component::DomainLabel(index)),
+    }
+}
+
+///
+/// Returns `part` if it can be searched for the location of an error, that is if comments are
+/// not allowed or `part` contains no comments or folding white space. Otherwise positions in the
+/// text with comments removed no longer correspond to positions in `part`.
+///
+pub(crate) fn uncommented(part: &str, options: Options) -> Option<&str> {
+    if !options.allow_comments {
+        Some(part)
+    } else if let Ok(Cow::Borrowed(stripped)) = strip_cfws(part) {
+        Some(stripped)
+    } else {
+        None
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Functions
+// ------------------------------------------------------------------------------------------------
+
+fn label_error(label: &str, options: Options) -> Option<Error> {
+    match parse_sub_domain(label) {
+        Err(e) => Some(e),
+        Ok(_) if !options.allow_smtputf8 && !label.is_ascii() => domain_to_ascii(label).err(),
+        Ok(_) => None,
+    }
+}
+
+fn find_non_ascii(part: &str) -> Option<(usize, char)> {
+    part.char_indices().find(|(_, c)| !c.is_ascii())
+}
+
+fn find_local_part_char(part: &str) -> Option<(usize, char)> {
+    if part.len() > 2 && part.starts_with(DQUOTE) && part.ends_with(DQUOTE) {
+        let mut char_iter = part[1..part.len() - 1].char_indices();
+        while let Some((index, c)) = char_iter.next() {
+            if c == ESC {
+                match char_iter.next() {
+                    Some((_, c2)) if is_vchar(c2) => (),
+                    Some((index2, c2)) => return Some((index2 + 1, c2)),
+                    None => return Some((index + 1, c)),
+                }
+            } else if !(is_wsp(c) || is_qtext_char(c)) {
+                return Some((index + 1, c));
+            }
+        }
+        None
+    } else {
+        let mut previous = None;
+        for (index, c) in part.char_indices() {
+            if c == DOT {
+                if previous.is_none() || previous == Some(DOT) {
+                    return Some((index, c));
+                }
+            } else if !is_atext(c) {
+                return Some((index, c));
+            }
+            previous = Some(c);
+        }
+        if previous == Some(DOT) {
+            Some((part.len() - 1, DOT))
+        } else {
+            None
+        }
+    }
+}
+
+fn find_label_char(label: &str) -> Option<(usize, char)> {
+    let mut chars = label.char_indices();
+    match (chars.next(), chars.next_back()) {
+        (Some((index, c)), _) if !c.is_alphanumeric() => Some((index, c)),
+        (_, Some((index, c))) if !c.is_alphanumeric() => Some((index, c)),
+        _ => label.char_indices().find(|(_, c)| !is_atext(*c)),
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Unit Tests
+// ------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::EmailAddress;
+
+    fn expect_error(
+        address: &str,
+        options: Options,
+        kind: Error,
+        span: Range<usize>,
+        found: Option<char>,
+        component: Component,
+    ) {
+        println!(">> <{}>, expecting {:?} at {:?}", address, kind, span);
+        assert_eq!(
+            EmailAddress::parse_detailed(address, options),
+            Err(ParseError::new(kind, span, found, component))
+        );
+    }
+
+    #[test]
+    fn test_address_errors() {
+        let options = Options::default();
+
+        expect_error(
+            "simon.example.com",
+            options,
+            Error::MissingSeparator,
+            0..17,
+            None,
+            Component::Address,
+        );
+        expect_error(
+            "Simon <simon@example.com",
+            options,
+            Error::MissingEndBracket,
+            24..24,
+            None,
+            Component::Address,
+        );
+        expect_error(
+            "<simon@example.com>",
+            options,
+            Error::MissingDisplayName,
+            0..1,
+            Some('<'),
+            Component::DisplayName,
+        );
+        expect_error(
+            "Simon <simon@example.com>",
+            options.without_display_text(),
+            Error::UnsupportedDisplayName,
+            0..5,
+            None,
+            Component::DisplayName,
+        );
+    }
+
+    #[test]
+    fn test_local_part_errors() {
+        let options = Options::default();
+
+        expect_error(
+            "Simon <sim,on@example.com>",
+            options,
+            Error::InvalidCharacter,
+            10..11,
+            Some(','),
+            Component::LocalPart,
+        );
+        expect_error(
+            "simon..j@example.com",
+            options,
+            Error::InvalidCharacter,
+            6..7,
+            Some('.'),
+            Component::LocalPart,
+        );
+        expect_error(
+            "simon.@example.com",
+            options,
+            Error::InvalidCharacter,
+            5..6,
+            Some('.'),
+            Component::LocalPart,
+        );
+        expect_error(
+            "\"sim\\\non\"@example.com",
+            options,
+            Error::InvalidCharacter,
+            5..6,
+            Some('\n'),
+            Component::LocalPart,
+        );
+        expect_error(
+            "@example.com",
+            options,
+            Error::LocalPartEmpty,
+            0..0,
+            None,
+            Component::LocalPart,
+        );
+        expect_error(
+            "sïmon@example.com",
+            options.without_smtputf8(),
+            Error::RequiresSmtpUtf8,
+            1..3,
+            Some('ï'),
+            Component::LocalPart,
+        );
+    }
+
+    #[test]
+    fn test_domain_errors() {
+        let options = Options::default();
+
+        expect_error(
+            "simon@example..com",
+            options,
+            Error::SubDomainEmpty,
+            14..14,
+            None,
+            Component::DomainLabel(1),
+        );
+        expect_error(
+            "simon@example.-com",
+            options,
+            Error::InvalidCharacter,
+            14..15,
+            Some('-'),
+            Component::DomainLabel(1),
+        );
+        expect_error(
+            "simon@example.co,m",
+            options,
+            Error::InvalidCharacter,
+            16..17,
+            Some(','),
+            Component::DomainLabel(1),
+        );
+        expect_error(
+            "simon@xn--zz.example.com",
+            options,
+            Error::InvalidALabel,
+            6..12,
+            None,
+            Component::DomainLabel(0),
+        );
+        expect_error(
+            "simon@[127.0.0.256]",
+            options,
+            Error::InvalidIPAddress,
+            6..19,
+            None,
+            Component::Domain,
+        );
+        expect_error(
+            "simon@localhost",
+            Options {
+                minimum_sub_domains: 2,
+                ..options
+            },
+            Error::DomainTooFew,
+            6..15,
+            None,
+            Component::Domain,
+        );
+    }
+
+    #[test]
+    fn test_errors_with_comments() {
+        let options = Options::default().with_comments();
+
+        expect_error(
+            "simon@exa,mple.com",
+            options,
+            Error::InvalidCharacter,
+            9..10,
+            Some(','),
+            Component::DomainLabel(0),
+        );
+        expect_error(
+            "simon@(work)exa,mple.com",
+            options,
+            Error::InvalidCharacter,
+            6..24,
+            None,
+            Component::Domain,
+        );
+    }
+
+    #[test]
+    fn test_display() {
+        let error =
+            EmailAddress::parse_detailed("simon@exa,mple.com", Options::default()).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Invalid character. Found ',' at byte 9 in the domain label 0."
+        );
+    }
+}
@@ -0,0 +1,385 @@
+/*!
+Support for provider-specific canonicalization of addresses.
+
+Many mail providers deliver mail for more than one form of the same mailbox, for example Gmail
+ignores any `.` characters in the `local-part`, delivers to `user+tag` as `user`, and treats
+`googlemail.com` as an alias for `gmail.com`. These rules are not part of any specification and
+so are not applied by [EmailAddress::canonical](crate::EmailAddress::canonical); instead a
+[ProviderRegistry] holds a set of [ProviderRules], one of which is selected by the domain of
+an address.
+
+The [DomainRules] type implements the common rules, and is used for each of the built-in
+providers; new rules for other domains may be registered either as a `DomainRules` value or as
+any other implementation of `ProviderRules`.
+*/
+
+use crate::{build_address, local_part_from_value, EmailAddress, Error};
+use std::fmt::Debug;
+
+// ------------------------------------------------------------------------------------------------
+// Public Types
+// ------------------------------------------------------------------------------------------------
+
+///
+/// The rules used by a mail provider to determine which addresses identify the same mailbox.
+///
+pub trait ProviderRules: Debug + Send + Sync {
+    ///
+    /// Returns `true` if these rules apply to addresses in `domain`. The domain is passed in
+    /// canonical form, lowercase and with any U-labels converted to A-labels.
+    ///
+    fn matches(&self, domain: &str) -> bool;
+
+    ///
+    /// Returns the provider-canonical form of `email`, which has already been converted to its
+    /// canonical form with [EmailAddress::canonical](crate::EmailAddress::canonical).
+    ///
+    fn canonicalize(&self, email: &EmailAddress) -> Result<EmailAddress, Error>;
+}
+
+///
+/// A common implementation of [ProviderRules] for a set of domains that deliver to the same
+/// mailboxes. An address whose `local-part` would be left empty by the rules, such as
+/// `+tag@example.com`, is not changed.
+///
+/// ```rust
+/// use email_address::*;
+/// use std::str::FromStr;
+///
+/// let rules = DomainRules::new(&["example.com", "example.org"])
+///     .with_canonical_domain("example.com")
+///     .with_subaddress_separator('-');
+///
+/// let email = EmailAddress::from_str("simon-lists@example.org").unwrap();
+///
+/// assert!(rules.matches(email.domain()));
+/// assert_eq!(rules.canonicalize(&email).unwrap().as_str(), "simon@example.com");
+/// ```
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DomainRules {
+    domains: Vec<String>,
+    canonical_domain: Option<String>,
+    subaddress_separator: Option<char>,
+    ignore_dots: bool,
+    lowercase_local_part: bool,
+}
+
+///
+/// A set of [ProviderRules], selected by the domain of an address. Rules registered later take
+/// precedence over those registered earlier, including the built-in rules.
+///
+/// ```rust
+/// use email_address::*;
+/// use std::str::FromStr;
+///
+/// let registry = ProviderRegistry::default();
+///
+/// let left = EmailAddress::from_str("j.o.h.n+promo@googlemail.com").unwrap();
+/// let right = EmailAddress::from_str("John <John@gmail.com>").unwrap();
+///
+/// assert_eq!(registry.canonicalize(&left).unwrap().as_str(), "john@gmail.com");
+/// assert!(registry.is_same_mailbox(&left, &right));
+/// ```
+///
+#[derive(Debug)]
+pub struct ProviderRegistry {
+    rules: Vec<Box<dyn ProviderRules>>,
+}
+
+// ------------------------------------------------------------------------------------------------
+// Implementations
+// ------------------------------------------------------------------------------------------------
+
+impl ProviderRules for DomainRules {
+    fn matches(&self, domain: &str) -> bool {
+        self.domains.iter().any(|d| d.eq_ignore_ascii_case(domain))
+    }
+
+    fn canonicalize(&self, email: &EmailAddress) -> Result<EmailAddress, Error> {
+        let mut value = match self.subaddress_separator {
+            Some(separator) => email.split_subaddress(separator).0,
+            None => email.local_part_unquoted(),
+        }
+        .into_owned();
+        if self.ignore_dots {
+            value.retain(|c| c != '.');
+        }
+        if self.lowercase_local_part {
+            value = value.to_lowercase();
+        }
+        if value.is_empty() {
+            // The rules do not apply to a local-part that is only a subaddress or dots.
+            return Ok(email.clone());
+        }
+        build_address(
+            "",
+            &local_part_from_value(&value),
+            self.canonical_domain.as_deref().unwrap_or(email.domain()),
+        )
+    }
+}
+
+impl DomainRules {
+    ///
+    /// Construct rules for the given set of `domains`, which by default does not change the
+    /// address.
+    ///
+    pub fn new(domains: &[&str]) -> Self {
+        Self {
+            domains: domains.iter().map(|d| d.to_lowercase()).collect(),
+            canonical_domain: None,
+            subaddress_separator: None,
+            ignore_dots: false,
+            lowercase_local_part: false,
+        }
+    }
+
+    /// Set the domain that replaces each of the domains matched by these rules.
+    pub fn with_canonical_domain(self, domain: &str) -> Self {
+        Self {
+            canonical_domain: Some(domain.to_lowercase()),
+            ..self
+        }
+    }
+
+    /// Set the separator of an RFC 5233 subaddress, any detail is removed.
+    pub fn with_subaddress_separator(self, separator: char) -> Self {
+        Self {
+            subaddress_separator: Some(separator),
+            ..self
+        }
+    }
+
+    /// Set the value of `ignore_dots` to `true`, any `.` is removed from the `local-part`.
+    pub fn with_ignored_dots(self) -> Self {
+        Self {
+            ignore_dots: true,
+            ..self
+        }
+    }
+
+    /// Set the value of `lowercase_local_part` to `true`, the `local-part` is lowercased.
+    pub fn with_lowercase_local_part(self) -> Self {
+        Self {
+            lowercase_local_part: true,
+            ..self
+        }
+    }
+
+    ///
+    /// Rules for Gmail; dots are ignored, `+` separates a subaddress, the `local-part` is
+    /// case-insensitive, and `googlemail.com` is an alias of `gmail.com`.
+    ///
+    pub fn gmail() -> Self {
+        Self::new(&["gmail.com", "googlemail.com"])
+            .with_canonical_domain("gmail.com")
+            .with_ignored_dots()
+            .with_subaddress_separator('+')
+            .with_lowercase_local_part()
+    }
+
+    ///
+    /// Rules for Outlook.com; `+` separates a subaddress and the `local-part` is
+    /// case-insensitive. Each domain has distinct mailboxes.
+    ///
+    pub fn outlook() -> Self {
+        Self::new(&["outlook.com", "hotmail.com", "live.com", "msn.com"])
+            .with_subaddress_separator('+')
+            .with_lowercase_local_part()
+    }
+
+    ///
+    /// Rules for iCloud Mail; `+` separates a subaddress, the `local-part` is
+    /// case-insensitive, and `me.com` and `mac.com` are aliases of `icloud.com`.
+    ///
+    pub fn icloud() -> Self {
+        Self::new(&["icloud.com", "me.com", "mac.com"])
+            .with_canonical_domain("icloud.com")
+            .with_subaddress_separator('+')
+            .with_lowercase_local_part()
+    }
+
+    ///
+    /// Rules for Proton Mail; `+` separates a subaddress, the `local-part` is
+    /// case-insensitive, and `protonmail.com` and `pm.me` are aliases of `proton.me`.
+    ///
+    pub fn proton() -> Self {
+        Self::new(&["proton.me", "protonmail.com", "protonmail.ch", "pm.me"])
+            .with_canonical_domain("proton.me")
+            .with_subaddress_separator('+')
+            .with_lowercase_local_part()
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl Default for ProviderRegistry {
+    fn default() -> Self {
+        Self::with_builtin_rules()
+    }
+}
+
+impl ProviderRegistry {
+    ///
+    /// Construct a registry with no rules, in which case `canonicalize` is equivalent to
+    /// `EmailAddress::canonical`. Use `Default` for a registry with the built-in rules.
+    ///
+    pub fn new() -> Self {
+        Self { rules: Vec::new() }
+    }
+
+    ///
+    /// Construct a registry with the rules for each of the built-in providers.
+    ///
+    pub fn with_builtin_rules() -> Self {
+        Self::new()
+            .with_rules(DomainRules::gmail())
+            .with_rules(DomainRules::outlook())
+            .with_rules(DomainRules::icloud())
+            .with_rules(DomainRules::proton())
+    }
+
+    ///
+    /// Add `rules` to the registry, these take precedence over any rules already registered
+    /// for the same domain.
+    ///
+    pub fn with_rules<R>(mut self, rules: R) -> Self
+    where
+        R: ProviderRules + 'static,
+    {
+        self.register(rules);
+        self
+    }
+
+    ///
+    /// Add `rules` to the registry, these take precedence over any rules already registered
+    /// for the same domain.
+    ///
+    pub fn register<R>(&mut self, rules: R)
+    where
+        R: ProviderRules + 'static,
+    {
+        self.rules.push(Box::new(rules));
+    }
+
+    ///
+    /// Returns the rules that apply to addresses in `domain`, if any.
+    ///
+    pub fn rules_for(&self, domain: &str) -> Option<&dyn ProviderRules> {
+        self.rules
+            .iter()
+            .rev()
+            .find(|rules| rules.matches(domain))
+            .map(|rules| rules.as_ref())
+    }
+
+    ///
+    /// Returns the provider-canonical form of `email`; the address is first converted to its
+    /// canonical form, and then by the rules for its domain if any are registered.
+    ///
+    pub fn canonicalize(&self, email: &EmailAddress) -> Result<EmailAddress, Error> {
+        let email = email.canonical()?;
+        match self.rules_for(email.domain()) {
+            Some(rules) => rules.canonicalize(&email),
+            None => Ok(email),
+        }
+    }
+
+    ///
+    /// Returns `true` if both addresses have the same provider-canonical form.
+    ///
+    pub fn is_same_mailbox(&self, left: &EmailAddress, right: &EmailAddress) -> bool {
+        match (self.canonicalize(left), self.canonicalize(right)) {
+            (Ok(left), Ok(right)) => left == right,
+            _ => false,
+        }
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Unit Tests
+// ------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    fn canonical(registry: &ProviderRegistry, address: &str) -> String {
+        registry
+            .canonicalize(&EmailAddress::from_str(address).unwrap())
+            .unwrap()
+            .to_string()
+    }
+
+    #[test]
+    fn test_builtin_rules() {
+        let registry = ProviderRegistry::default();
+
+        assert_eq!(
+            canonical(&registry, "J.o.h.n+promo@GoogleMail.com"),
+            "john@gmail.com"
+        );
+        assert_eq!(
+            canonical(&registry, "John.Doe+news@Hotmail.com"),
+            "john.doe@hotmail.com"
+        );
+        assert_eq!(
+            canonical(&registry, "john.doe-shopping@yahoo.com"),
+            "john.doe-shopping@yahoo.com"
+        );
+        assert_eq!(canonical(&registry, "+promo@gmail.com"), "+promo@gmail.com");
+        assert_eq!(
+            canonical(&registry, "\"...\"@gmail.com"),
+            "\"...\"@gmail.com"
+        );
+        assert_eq!(canonical(&registry, "John+x@me.com"), "john@icloud.com");
+        assert_eq!(canonical(&registry, "john+x@pm.me"), "john@proton.me");
+        assert_eq!(
+            canonical(&registry, "\"J.Doe+x\"@example.com"),
+            "J.Doe+x@example.com"
+        );
+    }
+
+    #[test]
+    fn test_same_mailbox() {
+        let registry = ProviderRegistry::default();
+        let email = |s: &str| EmailAddress::from_str(s).unwrap();
+
+        assert!(registry.is_same_mailbox(
+            &email("j.o.h.n+promo@googlemail.com"),
+            &email("john@gmail.com")
+        ));
+        assert!(!registry.is_same_mailbox(&email("john@hotmail.com"), &email("john@outlook.com")));
+        assert!(!registry.is_same_mailbox(&email("j.ohn@example.com"), &email("john@example.com")));
+        assert!(registry.is_same_mailbox(&email("+promo@gmail.com"), &email("+promo@gmail.com")));
+        assert!(!registry.is_same_mailbox(&email("+promo@gmail.com"), &email("+news@gmail.com")));
+    }
+
+    #[test]
+    fn test_registered_rules() {
+        let mut registry = ProviderRegistry::new();
+        assert_eq!(
+            canonical(&registry, "j.ohn+x@gmail.com"),
+            "j.ohn+x@gmail.com"
+        );
+
+        registry.register(
+            DomainRules::new(&["corp.example.com", "mail.example.com"])
+                .with_canonical_domain("example.com")
+                .with_subaddress_separator('+'),
+        );
+        assert_eq!(
+            canonical(&registry, "John+x@MAIL.example.com"),
+            "John@example.com"
+        );
+
+        let registry = ProviderRegistry::default()
+            .with_rules(DomainRules::new(&["gmail.com"]).with_lowercase_local_part());
+        assert_eq!(
+            canonical(&registry, "J.ohn+x@gmail.com"),
+            "j.ohn+x@gmail.com"
+        );
+    }
+}
@@ -0,0 +1,595 @@
+/*!
+Support for the paths used as the arguments of the SMTP `MAIL` and `RCPT` commands.
+
+From RFC 5321, §4.1.2. [Command Argument Syntax](https://tools.ietf.org/html/rfc5321#section-4.1.2):
+
+```ebnf
+Reverse-path    = Path / "<>"
+Forward-path    = Path
+Path            = "<" [ A-d-l ":" ] Mailbox ">"
+A-d-l           = At-domain *( "," At-domain )
+                ; Note that this form, the so-called "source
+                ; route", MUST BE accepted, SHOULD NOT be
+                ; generated, and SHOULD be ignored.
+At-domain       = "@" Domain
+Domain          = sub-domain *("." sub-domain)
+sub-domain      = Let-dig [Ldh-str]
+Mailbox         = Local-part "@" ( Domain / address-literal )
+Local-part      = Dot-string / Quoted-string
+                ; MAY be case-sensitive
+Dot-string      = Atom *("."  Atom)
+Atom            = 1*atext
+Quoted-string   = DQUOTE *QcontentSMTP DQUOTE
+QcontentSMTP    = qtextSMTP / quoted-pairSMTP
+quoted-pairSMTP = %d92 %d32-126
+                ; i.e., backslash followed by any ASCII
+                ; graphic (including itself) or SPace
+qtextSMTP       = %d32-33 / %d35-91 / %d93-126
+                ; i.e., within a quoted string, any
+                ; ASCII graphic or space is permitted
+                ; without blackslash-quoting except
+                ; double-quote and the backslash itself.
+```
+
+Unlike RFC 5322 there is no `CFWS`, a quoted string may not contain `HTAB` or folding white
+space, and a domain name is limited to letters, digits, and hyphens. RFC 6531 extends `atext`,
+`qtextSMTP`, and `sub-domain` to allow non-ASCII characters when the SMTPUTF8 extension is
+used. From §4.5.3.1.3, the total length of a path, including its punctuation, is limited to
+256 octets.
+*/
+
+use crate::{
+    domain_to_ascii, is_dot_atom_text, is_ldh_str, is_qtext_char, parse_domain_text, split_at,
+    EmailAddress, Error, Options, AT, DISPLAY_END, DISPLAY_START, DOT, DQUOTE, ESC, LBRACKET,
+    LOCAL_PART_MAX_LENGTH, SP,
+};
+use std::convert::TryFrom;
+use std::fmt::{Display, Formatter};
+use std::hash::{Hash, Hasher};
+use std::str::FromStr;
+
+// ------------------------------------------------------------------------------------------------
+// Public Types
+// ------------------------------------------------------------------------------------------------
+
+///
+/// Type representing an RFC 5321 `Path`, the argument of the `RCPT` command and, unless null, of
+/// the `MAIL` command. A path is a mailbox, an address without a display name or comments,
+/// optionally preceded by an obsolete source route. The `Display` implementation writes the path
+/// in SMTP command syntax, including the angle brackets.
+///
+/// ```rust
+/// use email_address::*;
+/// use std::str::FromStr;
+///
+/// let path = Path::from_str("<@relay.example.org,@mx.example.net:simon@example.com>").unwrap();
+///
+/// assert_eq!(path.source_route(), ["relay.example.org", "mx.example.net"]);
+/// assert_eq!(path.mailbox().as_str(), "simon@example.com");
+/// assert_eq!(format!("RCPT TO:{}", path.without_source_route()), "RCPT TO:<simon@example.com>");
+///
+/// assert_eq!(Path::from_str("simon@example.com"), Err(Error::InvalidPath));
+/// assert_eq!(Path::from_str("<\"simon\tsmith\"@example.com>"), Err(Error::InvalidCharacter));
+/// ```
+///
+/// As RFC 5321 requires that a source route is ignored, two paths are equal if their mailboxes
+/// are equal, whatever their source routes.
+///
+#[derive(Debug, Clone)]
+pub struct Path {
+    source_route: Vec<String>,
+    mailbox: EmailAddress,
+}
+
+///
+/// Type representing an RFC 5321 `Reverse-path`, the argument of the `MAIL` command, which is
+/// either a [Path] or the null path `<>` used by delivery status notifications and other
+/// messages that must not generate a bounce.
+///
+/// ```rust
+/// use email_address::*;
+/// use std::str::FromStr;
+///
+/// let reverse_path = ReversePath::from_str("<>").unwrap();
+///
+/// assert!(reverse_path.is_null());
+/// assert_eq!(reverse_path.mailbox(), None);
+/// assert_eq!(format!("MAIL FROM:{}", reverse_path), "MAIL FROM:<>");
+///
+/// let reverse_path = ReversePath::from_str("<simon@example.com>").unwrap();
+///
+/// assert_eq!(reverse_path.mailbox().unwrap().as_str(), "simon@example.com");
+/// ```
+///
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum ReversePath {
+    /// The null reverse-path, `<>`.
+    Null,
+    /// A reverse-path naming the mailbox to which errors are reported.
+    Path(Path),
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Types
+// ------------------------------------------------------------------------------------------------
+
+const PATH_MAX_LENGTH: usize = 256;
+
+const NULL_PATH: &str = "<>";
+
+const ROUTE_SEPARATOR: char = ',';
+const ROUTE_END: char = ':';
+
+// ------------------------------------------------------------------------------------------------
+// Implementations
+// ------------------------------------------------------------------------------------------------
+
+impl Display for Path {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", DISPLAY_START)?;
+        for (index, domain) in self.source_route.iter().enumerate() {
+            if index > 0 {
+                write!(f, "{}", ROUTE_SEPARATOR)?;
+            }
+            write!(f, "{}{}", AT, domain)?;
+        }
+        if !self.source_route.is_empty() {
+            write!(f, "{}", ROUTE_END)?;
+        }
+        write!(f, "{}{}", self.mailbox, DISPLAY_END)
+    }
+}
+
+impl PartialEq for Path {
+    fn eq(&self, other: &Self) -> bool {
+        self.mailbox == other.mailbox
+    }
+}
+
+impl Eq for Path {}
+
+impl Hash for Path {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.mailbox.hash(state);
+    }
+}
+
+impl FromStr for Path {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse_with_options(s, Default::default())
+    }
+}
+
+impl TryFrom<&EmailAddress> for Path {
+    type Error = Error;
+
+    ///
+    /// Convert the `email` part of an address, ignoring any display name or comments, into a
+    /// path without a source route. An error is returned if the address is valid for RFC 5322
+    /// but not for RFC 5321, such as a quoted string containing `HTAB`.
+    ///
+    fn try_from(email: &EmailAddress) -> Result<Self, Self::Error> {
+        let options = Options::default()
+            .with_no_minimum_sub_domains()
+            .with_general_literal();
+        Self::parse_with_options(
+            &format!(
+                "{}{}{}",
+                DISPLAY_START,
+                email.strip_comments().email(),
+                DISPLAY_END
+            ),
+            options,
+        )
+    }
+}
+
+impl TryFrom<EmailAddress> for Path {
+    type Error = Error;
+
+    fn try_from(email: EmailAddress) -> Result<Self, Self::Error> {
+        Self::try_from(&email)
+    }
+}
+
+impl From<Path> for EmailAddress {
+    fn from(path: Path) -> Self {
+        path.mailbox
+    }
+}
+
+impl Path {
+    ///
+    /// Parse a path with custom [Options]. The options for SMTPUTF8, domain literals, and the
+    /// minimum number of sub-domains apply to the mailbox; those for display names and
+    /// comments are ignored as neither may appear in a path.
+    ///
+    /// ```rust
+    /// use email_address::*;
+    ///
+    /// assert_eq!(
+    ///     Path::parse_with_options("<jöhn@example.com>", Options::default().without_smtputf8()),
+    ///     Err(Error::RequiresSmtpUtf8)
+    /// );
+    /// ```
+    ///
+    pub fn parse_with_options(path: &str, options: Options) -> Result<Self, Error> {
+        if path.len() > PATH_MAX_LENGTH {
+            return Error::PathTooLong.into();
+        }
+        let inner = path.strip_prefix(DISPLAY_START).ok_or(Error::InvalidPath)?;
+        let inner = inner
+            .strip_suffix(DISPLAY_END)
+            .ok_or(Error::MissingEndBracket)?;
+        let (source_route, mailbox) = if inner.starts_with(AT) {
+            let (route, mailbox) = inner.split_once(ROUTE_END).ok_or(Error::InvalidPath)?;
+            (parse_source_route(route, options)?, mailbox)
+        } else if inner.is_empty() {
+            return Error::InvalidPath.into();
+        } else {
+            (Vec::new(), inner)
+        };
+        parse_mailbox(mailbox, options)?;
+        Ok(Self {
+            source_route,
+            mailbox: EmailAddress::new_unchecked(mailbox),
+        })
+    }
+
+    ///
+    /// Returns the domains of the source route, if any, in the order written.
+    ///
+    pub fn source_route(&self) -> &[String] {
+        &self.source_route
+    }
+
+    ///
+    /// Returns the mailbox the path refers to.
+    ///
+    pub fn mailbox(&self) -> &EmailAddress {
+        &self.mailbox
+    }
+
+    ///
+    /// Consume the path, returning the mailbox it refers to.
+    ///
+    pub fn into_mailbox(self) -> EmailAddress {
+        self.mailbox
+    }
+
+    ///
+    /// Returns the path with the source route, if any, removed. RFC 5321 requires that a
+    /// source route is accepted but recommends that it is ignored and not generated.
+    ///
+    pub fn without_source_route(self) -> Self {
+        Self {
+            source_route: Vec::new(),
+            ..self
+        }
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl Display for ReversePath {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ReversePath::Null => write!(f, "{}", NULL_PATH),
+            ReversePath::Path(path) => path.fmt(f),
+        }
+    }
+}
+
+impl FromStr for ReversePath {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse_with_options(s, Default::default())
+    }
+}
+
+impl From<Path> for ReversePath {
+    fn from(path: Path) -> Self {
+        ReversePath::Path(path)
+    }
+}
+
+impl ReversePath {
+    ///
+    /// Parse a reverse-path with custom [Options], as for [Path::parse_with_options].
+    ///
+    pub fn parse_with_options(path: &str, options: Options) -> Result<Self, Error> {
+        if path == NULL_PATH {
+            Ok(ReversePath::Null)
+        } else {
+            Path::parse_with_options(path, options).map(ReversePath::Path)
+        }
+    }
+
+    ///
+    /// Returns `true` if this is the null reverse-path, `<>`.
+    ///
+    pub fn is_null(&self) -> bool {
+        matches!(self, ReversePath::Null)
+    }
+
+    ///
+    /// Returns the path, or `None` if this is the null reverse-path.
+    ///
+    pub fn path(&self) -> Option<&Path> {
+        match self {
+            ReversePath::Null => None,
+            ReversePath::Path(path) => Some(path),
+        }
+    }
+
+    ///
+    /// Returns the mailbox the path refers to, or `None` if this is the null reverse-path.
+    ///
+    pub fn mailbox(&self) -> Option<&EmailAddress> {
+        self.path().map(Path::mailbox)
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Functions
+// ------------------------------------------------------------------------------------------------
+
+fn parse_source_route(route: &str, options: Options) -> Result<Vec<String>, Error> {
+    route
+        .split(ROUTE_SEPARATOR)
+        .map(|at_domain| {
+            let domain = at_domain.strip_prefix(AT).ok_or(Error::InvalidPath)?;
+            if domain.starts_with(LBRACKET) {
+                return Error::InvalidPath.into();
+            }
+            parse_domain(domain, options)?;
+            Ok(domain.to_string())
+        })
+        .collect()
+}
+
+fn parse_mailbox(mailbox: &str, options: Options) -> Result<(), Error> {
+    let (local_part, domain) = split_at(mailbox)?;
+    parse_local_part(local_part, options)?;
+    parse_domain(domain, options)
+}
+
+fn parse_local_part(part: &str, options: Options) -> Result<(), Error> {
+    if part.is_empty() {
+        Error::LocalPartEmpty.into()
+    } else if part.len() > LOCAL_PART_MAX_LENGTH {
+        Error::LocalPartTooLong.into()
+    } else if part.len() > 1 && part.starts_with(DQUOTE) && part.ends_with(DQUOTE) {
+        if part.len() == 2 {
+            Error::LocalPartEmpty.into()
+        } else if is_qcontent_smtp(&part[1..part.len() - 1]) {
+            check_smtputf8(part, options)
+        } else {
+            Error::InvalidCharacter.into()
+        }
+    } else if is_dot_atom_text(part) {
+        check_smtputf8(part, options)
+    } else {
+        Error::InvalidCharacter.into()
+    }
+}
+
+fn parse_domain(part: &str, options: Options) -> Result<(), Error> {
+    parse_domain_text(part, options)?;
+    if !part.starts_with(LBRACKET) {
+        for label in part.split(DOT) {
+            if label.is_ascii() {
+                if !is_ldh_str(label) {
+                    return Error::InvalidCharacter.into();
+                }
+            } else {
+                let _ = domain_to_ascii(label)?;
+            }
+        }
+    }
+    check_smtputf8(part, options)
+}
+
+fn check_smtputf8(part: &str, options: Options) -> Result<(), Error> {
+    if !options.allow_smtputf8 && !part.is_ascii() {
+        Error::RequiresSmtpUtf8.into()
+    } else {
+        Ok(())
+    }
+}
+
+fn is_qcontent_smtp(s: &str) -> bool {
+    let mut char_iter = s.chars();
+    while let Some(c) = char_iter.next() {
+        if c == ESC {
+            // quoted-pairSMTP
+            match char_iter.next() {
+                Some(c2) if (SP..='\x7E').contains(&c2) => (),
+                _ => return false,
+            }
+        } else if !(c == SP || is_qtext_char(c)) {
+            // qtextSMTP
+            return false;
+        }
+    }
+    true
+}
+
+// ------------------------------------------------------------------------------------------------
+// Unit Tests
+// ------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::hash_map::DefaultHasher;
+
+    fn path(text: &str) -> Result<String, Error> {
+        Path::from_str(text).map(|path| path.to_string())
+    }
+
+    #[test]
+    fn test_paths() {
+        assert_eq!(
+            path("<simon@example.com>"),
+            Ok("<simon@example.com>".to_string())
+        );
+        assert_eq!(
+            path("<\"simon smith\"@example.com>"),
+            Ok("<\"simon smith\"@example.com>".to_string())
+        );
+        assert_eq!(
+            path("<\"simon\\ smith\"@example.com>"),
+            Ok("<\"simon\\ smith\"@example.com>".to_string())
+        );
+        assert_eq!(
+            path("<simon@[192.168.2.1]>"),
+            Ok("<simon@[192.168.2.1]>".to_string())
+        );
+        assert_eq!(
+            path("<@a.example,@b.example:simon@c.example>"),
+            Ok("<@a.example,@b.example:simon@c.example>".to_string())
+        );
+        assert_eq!(
+            path("<simon@bücher.example>"),
+            Ok("<simon@bücher.example>".to_string())
+        );
+    }
+
+    #[test]
+    fn test_invalid_paths() {
+        assert_eq!(path("<>"), Err(Error::InvalidPath));
+        assert_eq!(path("simon@example.com"), Err(Error::InvalidPath));
+        assert_eq!(path("<simon@example.com"), Err(Error::MissingEndBracket));
+        assert_eq!(path("<simon>"), Err(Error::MissingSeparator));
+        assert_eq!(path("<@a.example>"), Err(Error::InvalidPath));
+        assert_eq!(
+            path("<a.example:simon@example.com>"),
+            Err(Error::InvalidCharacter)
+        );
+        assert_eq!(
+            path("<@a.example,b.example:simon@example.com>"),
+            Err(Error::InvalidPath)
+        );
+        assert_eq!(
+            path("<@[192.168.2.1]:simon@example.com>"),
+            Err(Error::InvalidPath)
+        );
+        assert_eq!(path("<@:simon@example.com>"), Err(Error::DomainEmpty));
+        assert_eq!(path("< simon@example.com>"), Err(Error::InvalidCharacter));
+        assert_eq!(path("<simon@example.com >"), Err(Error::InvalidCharacter));
+        assert_eq!(
+            path("<Simon <simon@example.com>>"),
+            Err(Error::InvalidCharacter)
+        );
+    }
+
+    #[test]
+    fn test_stricter_than_rfc_5322() {
+        assert!(EmailAddress::is_valid("\"simon\tsmith\"@example.com"));
+        assert_eq!(
+            path("<\"simon\tsmith\"@example.com>"),
+            Err(Error::InvalidCharacter)
+        );
+        assert!(EmailAddress::is_valid("simon@exam_ple.com"));
+        assert_eq!(path("<simon@exam_ple.com>"), Err(Error::InvalidCharacter));
+        assert_eq!(
+            Path::parse_with_options("<simon@example.com>", Options::default().with_comments())
+                .map(|path| path.to_string()),
+            Ok("<simon@example.com>".to_string())
+        );
+        assert_eq!(
+            Path::parse_with_options(
+                "<simon(comment)@example.com>",
+                Options::default().with_comments()
+            ),
+            Err(Error::InvalidCharacter)
+        );
+    }
+
+    #[test]
+    fn test_smtputf8() {
+        let options = Options::default().without_smtputf8();
+        assert_eq!(
+            Path::parse_with_options("<jöhn@example.com>", options),
+            Err(Error::RequiresSmtpUtf8)
+        );
+        assert_eq!(
+            Path::parse_with_options("<john@bücher.example>", options),
+            Err(Error::RequiresSmtpUtf8)
+        );
+        assert!(Path::parse_with_options("<john@xn--bcher-kva.example>", options).is_ok());
+        assert_eq!(path("<john@xn--ls8h.example>"), Err(Error::InvalidALabel));
+    }
+
+    #[test]
+    fn test_length_limit() {
+        let domain = format!("{}.{}.{}", "a".repeat(63), "b".repeat(63), "c".repeat(63));
+        let local_part = "d".repeat(64);
+        let text = format!("<{}@{}>", local_part, domain);
+        assert_eq!(text.len(), 258);
+        assert_eq!(path(&text), Err(Error::PathTooLong));
+        let text = format!("<{}@{}>", &local_part[..62], domain);
+        assert_eq!(text.len(), PATH_MAX_LENGTH);
+        assert_eq!(path(&text), Ok(text.clone()));
+        let text = format!("<@example.com:{}@{}>", &local_part[..50], domain);
+        assert_eq!(text.len(), PATH_MAX_LENGTH + 1);
+        assert_eq!(path(&text), Err(Error::PathTooLong));
+    }
+
+    #[test]
+    fn test_reverse_path() {
+        assert_eq!(ReversePath::from_str("<>"), Ok(ReversePath::Null));
+        assert_eq!(ReversePath::Null.to_string(), "<>");
+        let reverse_path = ReversePath::from_str("<@a.example:simon@example.com>").unwrap();
+        assert!(!reverse_path.is_null());
+        assert_eq!(
+            reverse_path.path().map(Path::source_route),
+            Some(&["a.example".to_string()][..])
+        );
+        assert_eq!(
+            reverse_path.to_string(),
+            "<@a.example:simon@example.com>".to_string()
+        );
+        assert_eq!(ReversePath::from_str("< >"), Err(Error::MissingSeparator));
+        assert_eq!(ReversePath::from_str(""), Err(Error::InvalidPath));
+    }
+
+    #[test]
+    fn test_path_equality() {
+        let hash_of = |path: &Path| {
+            let mut hasher = DefaultHasher::new();
+            path.hash(&mut hasher);
+            hasher.finish()
+        };
+        let routed = Path::from_str("<@A.example:simon@example.com>").unwrap();
+        let other = Path::from_str("<@a.example,@b.example:simon@EXAMPLE.com>").unwrap();
+
+        assert_eq!(routed, other);
+        assert_eq!(hash_of(&routed), hash_of(&other));
+        assert_eq!(routed, Path::from_str("<simon@example.com>").unwrap());
+        assert_ne!(
+            routed,
+            Path::from_str("<@a.example:jane@example.com>").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_from_email_address() {
+        let email = EmailAddress::from_str("Simon <simon@example.com>").unwrap();
+        assert_eq!(
+            Path::try_from(&email).map(|path| path.to_string()),
+            Ok("<simon@example.com>".to_string())
+        );
+        let email = EmailAddress::from_str("\"simon\tsmith\"@example.com").unwrap();
+        assert_eq!(Path::try_from(email), Err(Error::InvalidCharacter));
+        let path = Path::from_str("<@a.example:simon@example.com>").unwrap();
+        assert_eq!(
+            EmailAddress::from(path),
+            EmailAddress::from_str("simon@example.com").unwrap()
+        );
+    }
+}
@@ -0,0 +1,105 @@
+//
+// Checks that validating and comparing a borrowed `EmailAddressRef` does not
+// allocate. A global allocator counts the allocations made by each thread, so
+// that tests running in parallel do not affect each other's counts.
+//
+
+use email_address::*;
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+struct CountingAllocator;
+
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let _ = ALLOCATIONS.try_with(|count| count.set(count.get() + 1));
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(ptr, layout) }
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+fn allocations<T>(f: impl FnOnce() -> T) -> usize {
+    let before = ALLOCATIONS.with(Cell::get);
+    let _ = f();
+    ALLOCATIONS.with(Cell::get) - before
+}
+
+#[test]
+fn test_parse_does_not_allocate() {
+    let options = Options::default().with_comments().with_general_literal();
+    for address in &[
+        "simon@example.com",
+        "Simon Johnston <simon.johnston@mail.example.com>",
+        "\"simon johnston\"@example.com",
+        "simon(work)@example.com (Simon)",
+        "(start) simon . johnston @ mail . example . com",
+        "dörte@sörensen.example.com",
+        "simon@[127.0.0.1]",
+        "simon@[IPv6:2001:db8::ffff:192.0.2.1]",
+        "simon@[x400:c=gb]",
+    ] {
+        assert_eq!(
+            allocations(|| EmailAddressRef::parse_with_options(address, options).unwrap()),
+            0,
+            "{:?}",
+            address
+        );
+    }
+    // An owned address copies the string, showing that allocations are counted.
+    assert_eq!(
+        allocations(|| EmailAddress::parse_with_options("simon@example.com", options).unwrap()),
+        1
+    );
+}
+
+#[test]
+fn test_invalid_parse_does_not_allocate() {
+    let options = Options::default().with_comments();
+    let too_long = format!("(comment) {}@example.com", "s".repeat(65));
+    for address in &[
+        too_long.as_str(),
+        "simon",
+        "simon@",
+        "sim..on@example.com",
+        "simon@exa mple.com",
+        "simon(work@example.com",
+        "simon@[IPv6:1:2:3:4:5:6:7:8:9]",
+    ] {
+        assert_eq!(
+            allocations(|| EmailAddressRef::parse_with_options(address, options).unwrap_err()),
+            0,
+            "{:?}",
+            address
+        );
+    }
+}
+
+#[test]
+fn test_compare_does_not_allocate() {
+    let options = Options::default().with_comments();
+    let email = EmailAddressRef::parse_with_options("\"simon\"@Example.COM", options).unwrap();
+    let other = EmailAddressRef::parse_with_options("simon(work)@example.com", options).unwrap();
+
+    assert_eq!(allocations(|| email == other), 0);
+    assert_eq!(allocations(|| email.cmp(&other)), 0);
+    assert_eq!(
+        allocations(|| {
+            let mut hasher = DefaultHasher::new();
+            email.hash(&mut hasher);
+            hasher.finish()
+        }),
+        0
+    );
+}